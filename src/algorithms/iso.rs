@@ -0,0 +1,120 @@
+use petgraph::graph::{Graph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use crate::algorithms::vf2::{find_mappings, find_first_mapping_by};
+
+/// Returns all isomorphisms from `a` to `b` as node maps; use
+/// `find_isomorphism` when one is enough.
+///
+/// Node and edge labels must agree, and edges are compared with their
+/// multiplicities, so parallel edges and self-loops are taken into account.
+pub fn isomorphisms<N, E>(a: &Graph<N, E>, b: &Graph<N, E>) -> Vec<HashMap<NodeIndex, NodeIndex>>
+where
+    N: Eq,
+    E: Eq + Clone,
+{
+    if a.node_count() != b.node_count() || a.edge_count() != b.edge_count() {
+        return Vec::new();
+    }
+    find_mappings(a, b, true)
+        .into_iter()
+        .filter(|f| preserves_edges(a, b, f))
+        .collect()
+}
+
+/// Returns one isomorphism from `a` to `b` as a node map, if any, stopping
+/// the search at the first one found.
+pub fn find_isomorphism<N, E>(a: &Graph<N, E>, b: &Graph<N, E>) -> Option<HashMap<NodeIndex, NodeIndex>>
+where
+    N: Eq,
+    E: Eq + Clone,
+{
+    find_isomorphism_by(a, b, |_| true)
+}
+
+/// Returns the first isomorphism from `a` to `b` that `accept` takes, if
+/// any, stopping the search there.
+pub fn find_isomorphism_by<N, E>(
+    a: &Graph<N, E>,
+    b: &Graph<N, E>,
+    mut accept: impl FnMut(&HashMap<NodeIndex, NodeIndex>) -> bool,
+) -> Option<HashMap<NodeIndex, NodeIndex>>
+where
+    N: Eq,
+    E: Eq + Clone,
+{
+    if a.node_count() != b.node_count() || a.edge_count() != b.edge_count() {
+        return None;
+    }
+    find_first_mapping_by(a, b, |x, y| x == y, |x, y| x == y, true, |f| preserves_edges(a, b, f) && accept(f))
+}
+
+/// Checks whether `a` and `b` are isomorphic as labelled graphs.
+pub fn is_isomorphic<N, E>(a: &Graph<N, E>, b: &Graph<N, E>) -> bool
+where
+    N: Eq,
+    E: Eq + Clone,
+{
+    find_isomorphism(a, b).is_some()
+}
+
+/// Checks that `f` maps the edges of `a` one-to-one onto the edges of `b`.
+fn preserves_edges<N, E: Eq>(a: &Graph<N, E>, b: &Graph<N, E>, f: &HashMap<NodeIndex, NodeIndex>) -> bool {
+    let mut remaining: Vec<_> = b
+        .edge_indices()
+        .map(|e| (b.edge_endpoints(e).unwrap(), &b[e]))
+        .collect();
+    a.edge_indices().all(|e| {
+        let (src, dst) = a.edge_endpoints(e).unwrap();
+        let image = ((f[&src], f[&dst]), &a[e]);
+        match remaining.iter().position(|r| *r == image) {
+            Some(i) => {
+                remaining.swap_remove(i);
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+/// A hash of `g` that is invariant under isomorphism.
+///
+/// Node colours start from the node labels and are refined by a few rounds
+/// of Weisfeiler–Lehman over labelled in- and out-edges; the hash combines
+/// the sorted colours. Isomorphic graphs always hash equal, but equal
+/// hashes only suggest isomorphism, so confirm with `is_isomorphic`.
+pub fn canonical_hash<N, E>(g: &Graph<N, E>) -> u64
+where
+    N: Hash,
+    E: Hash,
+{
+    let mut colours: Vec<u64> = g.node_indices().map(|n| hash_of(&g[n])).collect();
+    for _ in 0..3 {
+        colours = g
+            .node_indices()
+            .map(|n| {
+                let mut around: Vec<(bool, u64, u64)> = g
+                    .edges_directed(n, Direction::Outgoing)
+                    .map(|e| (true, hash_of(e.weight()), colours[e.target().index()]))
+                    .chain(
+                        g.edges_directed(n, Direction::Incoming)
+                            .map(|e| (false, hash_of(e.weight()), colours[e.source().index()])),
+                    )
+                    .collect();
+                around.sort_unstable();
+                hash_of(&(colours[n.index()], around))
+            })
+            .collect();
+    }
+    colours.sort_unstable();
+    hash_of(&(g.node_count(), g.edge_count(), colours))
+}
+
+fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
@@ -1,3 +1,7 @@
 pub mod vf2;
+pub mod iso;
+pub mod rng;
 
-pub use vf2::{find_mappings, vf2_subgraph_isomorphism};
\ No newline at end of file
+pub use vf2::{find_mappings, find_mappings_by, find_first_mapping_by, vf2_subgraph_isomorphism};
+pub use iso::{is_isomorphic, isomorphisms, find_isomorphism, find_isomorphism_by, canonical_hash};
+pub use rng::SplitMix64;
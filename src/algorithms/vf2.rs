@@ -3,21 +3,25 @@ use petgraph::Direction;
 use std::collections::HashMap;
 
 /// Checks if mapping a pattern node `p` to a graph node `g` is feasible given the current mapping.
-/// If `check_edge_labels` is true, then it also checks that the edge weights (or labels) match.
-fn is_feasible<N, E>(
+/// Node compatibility is decided by `node_match`; if `check_edge_labels` is true, then edge
+/// weights (or labels) are compared with `edge_match`.
+#[allow(clippy::too_many_arguments)]
+fn is_feasible<PN, PE, N, E, FN, FE>(
     mapping: &HashMap<NodeIndex, NodeIndex>,
-    pattern: &Graph<N, E>,
+    pattern: &Graph<PN, PE>,
     graph: &Graph<N, E>,
     p: NodeIndex,
     g: NodeIndex,
+    node_match: &FN,
+    edge_match: &FE,
     check_edge_labels: bool,
 ) -> bool
 where
-    N: Eq,
-    E: Eq,
+    FN: Fn(&PN, &N) -> bool,
+    FE: Fn(&PE, &E) -> bool,
 {
-    // Check node label equality
-    if pattern[p] != graph[g] {
+    // Check node label compatibility
+    if !node_match(&pattern[p], &graph[g]) {
         return false;
     }
 
@@ -29,7 +33,7 @@ where
                 let pat_weight = pattern.edge_weight(pat_edge_idx).expect("Missing pattern edge weight");
                 if let Some(graph_edge_idx) = graph.find_edge(g, g_neighbor) {
                     let graph_weight = graph.edge_weight(graph_edge_idx).expect("Missing graph edge weight");
-                    if pat_weight != graph_weight {
+                    if !edge_match(pat_weight, graph_weight) {
                         return false;
                     }
                 } else {
@@ -49,7 +53,7 @@ where
                 let pat_weight = pattern.edge_weight(pat_edge_idx).expect("Missing pattern edge weight");
                 if let Some(graph_edge_idx) = graph.find_edge(g_neighbor, g) {
                     let graph_weight = graph.edge_weight(graph_edge_idx).expect("Missing graph edge weight");
-                    if pat_weight != graph_weight {
+                    if !edge_match(pat_weight, graph_weight) {
                         return false;
                     }
                 } else {
@@ -65,8 +69,8 @@ where
 }
 
 /// Generates candidate pairs (pattern node, graph node) for mapping.
-fn candidate_pairs<N, E>(
-    pattern: &Graph<N, E>,
+fn candidate_pairs<PN, PE, N, E>(
+    pattern: &Graph<PN, PE>,
     graph: &Graph<N, E>,
     mapping: &HashMap<NodeIndex, NodeIndex>,
 ) -> Vec<(NodeIndex, NodeIndex)> {
@@ -87,27 +91,60 @@ fn candidate_pairs<N, E>(
 }
 
 /// Recursively collects all complete mappings (node index maps) from pattern to graph.
-fn search_collect<N, E>(
-    pattern: &Graph<N, E>,
+fn search_collect<PN, PE, N, E, FN, FE>(
+    pattern: &Graph<PN, PE>,
     graph: &Graph<N, E>,
     mapping: &mut HashMap<NodeIndex, NodeIndex>,
+    node_match: &FN,
+    edge_match: &FE,
     check_edge_labels: bool,
     results: &mut Vec<HashMap<NodeIndex, NodeIndex>>,
 ) where
-    N: Eq,
-    E: Eq + Clone,
+    FN: Fn(&PN, &N) -> bool,
+    FE: Fn(&PE, &E) -> bool,
 {
     if mapping.len() == pattern.node_count() {
         results.push(mapping.clone());
         return;
     }
     for (p, g) in candidate_pairs(pattern, graph, mapping) {
-        if is_feasible(mapping, pattern, graph, p, g, check_edge_labels) {
+        if is_feasible(mapping, pattern, graph, p, g, node_match, edge_match, check_edge_labels) {
+            mapping.insert(p, g);
+            search_collect(pattern, graph, mapping, node_match, edge_match, check_edge_labels, results);
+            mapping.remove(&p);
+        }
+    }
+}
+
+/// Recursively searches for the first complete mapping that `accept` takes,
+/// leaving it in `mapping`. Returns whether one was found.
+fn search_first<PN, PE, N, E, FN, FE, FA>(
+    pattern: &Graph<PN, PE>,
+    graph: &Graph<N, E>,
+    mapping: &mut HashMap<NodeIndex, NodeIndex>,
+    node_match: &FN,
+    edge_match: &FE,
+    check_edge_labels: bool,
+    accept: &mut FA,
+) -> bool
+where
+    FN: Fn(&PN, &N) -> bool,
+    FE: Fn(&PE, &E) -> bool,
+    FA: FnMut(&HashMap<NodeIndex, NodeIndex>) -> bool,
+{
+    if mapping.len() == pattern.node_count() {
+        return accept(mapping);
+    }
+    for (p, g) in candidate_pairs(pattern, graph, mapping) {
+        if is_feasible(mapping, pattern, graph, p, g, node_match, edge_match, check_edge_labels) {
             mapping.insert(p, g);
-            search_collect(pattern, graph, mapping, check_edge_labels, results);
+            if search_first(pattern, graph, mapping, node_match, edge_match, check_edge_labels, accept) {
+                return true;
+            }
             mapping.remove(&p);
         }
     }
+    false
 }
 
 /// Returns all node mapping solutions of subgraph isomorphisms from `pattern` to `graph`.
@@ -119,13 +156,60 @@ pub fn find_mappings<N, E>(
 where
     N: Eq,
     E: Eq + Clone,
+{
+    find_mappings_by(pattern, graph, |p, g| p == g, |p, g| p == g, check_edge_labels)
+}
+
+/// Returns all node mapping solutions from `pattern` to `graph`, deciding label
+/// compatibility with the given predicates instead of equality.
+///
+/// The pattern may carry a different label type than the host graph, which lets
+/// callers match patterns containing wildcards or variables.
+pub fn find_mappings_by<PN, PE, N, E, FN, FE>(
+    pattern: &Graph<PN, PE>,
+    graph: &Graph<N, E>,
+    node_match: FN,
+    edge_match: FE,
+    check_edge_labels: bool,
+) -> Vec<HashMap<NodeIndex, NodeIndex>>
+where
+    FN: Fn(&PN, &N) -> bool,
+    FE: Fn(&PE, &E) -> bool,
 {
     let mut results = Vec::new();
     let mut mapping = HashMap::new();
-    search_collect(pattern, graph, &mut mapping, check_edge_labels, &mut results);
+    search_collect(
+        pattern,
+        graph,
+        &mut mapping,
+        &node_match,
+        &edge_match,
+        check_edge_labels,
+        &mut results,
+    );
     results
 }
 
+/// Returns the first node mapping from `pattern` to `graph` that `accept`
+/// takes, stopping the search there.
+pub fn find_first_mapping_by<PN, PE, N, E, FN, FE, FA>(
+    pattern: &Graph<PN, PE>,
+    graph: &Graph<N, E>,
+    node_match: FN,
+    edge_match: FE,
+    check_edge_labels: bool,
+    mut accept: FA,
+) -> Option<HashMap<NodeIndex, NodeIndex>>
+where
+    FN: Fn(&PN, &N) -> bool,
+    FE: Fn(&PE, &E) -> bool,
+    FA: FnMut(&HashMap<NodeIndex, NodeIndex>) -> bool,
+{
+    let mut mapping = HashMap::new();
+    search_first(pattern, graph, &mut mapping, &node_match, &edge_match, check_edge_labels, &mut accept)
+        .then_some(mapping)
+}
+
 /// Returns true if at least one subgraph isomorphism exists.
 pub fn vf2_subgraph_isomorphism<N, E>(
     pattern: &Graph<N, E>,
@@ -136,5 +220,5 @@ where
     N: Eq,
     E: Eq + Clone,
 {
-    !find_mappings(pattern, graph, check_edge_labels).is_empty()
+    find_first_mapping_by(pattern, graph, |p, g| p == g, |p, g| p == g, check_edge_labels, |_| true).is_some()
 }
@@ -0,0 +1,200 @@
+use petgraph::graph::{Graph, NodeIndex};
+use std::collections::HashMap;
+use std::hash::Hash;
+use crate::algorithms::iso::{canonical_hash, find_isomorphism_by};
+use crate::analysis::critical_pairs::{critical_pairs, CriticalPair};
+use crate::transformation::{Rule, Morphism, RuleError};
+use crate::transformation::dpo::{find_matches, apply_once};
+
+/// Limits for the joinability search on each side of a critical pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JoinLimits {
+    /// Maximum number of rewrite steps after the conflicting step.
+    pub max_depth: usize,
+    /// Maximum number of distinct graphs explored per side.
+    pub max_states: usize,
+}
+
+impl Default for JoinLimits {
+    fn default() -> Self {
+        Self { max_depth: 3, max_states: 1000 }
+    }
+}
+
+/// Outcome of the joinability search for one critical pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Joinability {
+    /// Both sides rewrite to isomorphic graphs, and the isomorphism agrees
+    /// on every node of the overlap preserved by both conflicting steps.
+    StrictlyJoinable,
+    /// Both sides were explored completely without finding a join.
+    NotJoinable,
+    /// No join was found within the limits.
+    BudgetExceeded,
+}
+
+/// A critical pair of `rules[first]` and `rules[second]` with its verdict.
+#[derive(Debug, Clone)]
+pub struct PairVerdict<N, E> {
+    pub first: usize,
+    pub second: usize,
+    pub pair: CriticalPair<N, E>,
+    pub joinability: Joinability,
+}
+
+/// Check the local confluence of a rule set by trying to join every
+/// critical pair of every ordered pair of rules.
+///
+/// The rule set is locally confluent if every verdict is
+/// `Joinability::StrictlyJoinable`.
+///
+/// # Errors
+/// Returns the `RuleError` of the first malformed rule.
+pub fn check_local_confluence<N, E>(
+    rules: &[Rule<N, E>],
+    limits: JoinLimits,
+) -> Result<Vec<PairVerdict<N, E>>, RuleError>
+where
+    N: Eq + Clone + Hash,
+    E: Eq + Clone + Hash,
+{
+    let mut verdicts = Vec::new();
+    for (first, r1) in rules.iter().enumerate() {
+        for (second, r2) in rules.iter().enumerate() {
+            for pair in critical_pairs(r1, r2, first == second)? {
+                let joinability = join(rules, r1, r2, &pair, limits)?;
+                verdicts.push(PairVerdict { first, second, pair, joinability });
+            }
+        }
+    }
+    Ok(verdicts)
+}
+
+/// Try to join the two sides of the critical pair `pair` of `r1` and `r2`
+/// by rewriting both with `rules` up to the given limits.
+///
+/// # Errors
+/// Returns the `RuleError` of the first malformed rule.
+pub fn join<N, E>(
+    rules: &[Rule<N, E>],
+    r1: &Rule<N, E>,
+    r2: &Rule<N, E>,
+    pair: &CriticalPair<N, E>,
+    limits: JoinLimits,
+) -> Result<Joinability, RuleError>
+where
+    N: Eq + Clone + Hash,
+    E: Eq + Clone + Hash,
+{
+    let (Ok(left), Ok(right)) = (
+        apply_once(r1, &pair.graph, &pair.m1),
+        apply_once(r2, &pair.graph, &pair.m2),
+    ) else {
+        // Critical pairs are applicable by construction
+        return Ok(Joinability::NotJoinable);
+    };
+
+    // Overlap nodes that survive both conflicting steps must be joined consistently
+    let persistent: Vec<NodeIndex> = pair
+        .graph
+        .node_indices()
+        .filter(|n| left.tracking.map_node(n).is_some() && right.tracking.map_node(n).is_some())
+        .collect();
+
+    let left_start = State::new(left.graph, left.tracking);
+    let right_start = State::new(right.graph, right.tracking);
+    let (left_states, left_complete) = explore(rules, left_start, &persistent, limits)?;
+    let (right_states, right_complete) = explore(rules, right_start, &persistent, limits)?;
+
+    let mut buckets: HashMap<u64, Vec<&State<N, E>>> = HashMap::new();
+    for r in &right_states {
+        buckets.entry(r.hash).or_default().push(r);
+    }
+    let joined = left_states
+        .iter()
+        .any(|l| buckets.get(&l.hash).is_some_and(|b| b.iter().any(|r| agrees(l, r, &persistent))));
+    Ok(if joined {
+        Joinability::StrictlyJoinable
+    } else if left_complete && right_complete {
+        Joinability::NotJoinable
+    } else {
+        Joinability::BudgetExceeded
+    })
+}
+
+/// A graph reached from one side of a critical pair, with the tracking of
+/// the overlap graph into it and its `canonical_hash`.
+struct State<N, E> {
+    graph: Graph<N, E>,
+    tracking: Morphism,
+    hash: u64,
+}
+
+impl<N: Hash, E: Hash> State<N, E> {
+    fn new(graph: Graph<N, E>, tracking: Morphism) -> Self {
+        State { hash: canonical_hash(&graph), graph, tracking }
+    }
+}
+
+/// Collect the states reachable from `start` breadth-first, up to the
+/// limits, bucketed by `canonical_hash`. Returns whether the search covered
+/// every reachable state.
+fn explore<N, E>(
+    rules: &[Rule<N, E>],
+    start: State<N, E>,
+    persistent: &[NodeIndex],
+    limits: JoinLimits,
+) -> Result<(Vec<State<N, E>>, bool), RuleError>
+where
+    N: Eq + Clone + Hash,
+    E: Eq + Clone + Hash,
+{
+    let mut buckets: HashMap<u64, Vec<usize>> = HashMap::from([(start.hash, vec![0])]);
+    let mut states = vec![start];
+    let mut frontier = vec![0];
+    let mut complete = true;
+    for depth in 0..=limits.max_depth {
+        let mut next = Vec::new();
+        for i in frontier {
+            for rule in rules {
+                for m in find_matches(rule, &states[i].graph, true)? {
+                    let Ok(d) = apply_once(rule, &states[i].graph, &m) else {
+                        continue;
+                    };
+                    if depth == limits.max_depth || states.len() >= limits.max_states {
+                        complete = false;
+                        continue;
+                    }
+                    let state = State::new(d.graph, d.tracking.compose(&states[i].tracking));
+                    let bucket = buckets.entry(state.hash).or_default();
+                    if !bucket.iter().any(|s| agrees(&states[*s], &state, persistent)) {
+                        bucket.push(states.len());
+                        next.push(states.len());
+                        states.push(state);
+                    }
+                }
+            }
+        }
+        frontier = next;
+    }
+    Ok((states, complete))
+}
+
+/// Whether two states are isomorphic by an isomorphism that respects the
+/// tracking of the persistent overlap nodes.
+fn agrees<N, E>(a: &State<N, E>, b: &State<N, E>, persistent: &[NodeIndex]) -> bool
+where
+    N: Eq,
+    E: Eq + Clone,
+{
+    find_isomorphism_by(&a.graph, &b.graph, |f| {
+        persistent.iter().all(|n| {
+            match (a.tracking.map_node(n), b.tracking.map_node(n)) {
+                (Some(x), Some(y)) => f.get(x) == Some(y),
+                (None, None) => true,
+                _ => false,
+            }
+        })
+    })
+    .is_some()
+}
@@ -0,0 +1,139 @@
+use petgraph::graph::Graph;
+use crate::transformation::{Rule, Morphism, MergePolicy, RuleError};
+use crate::transformation::dpo::{apply_once, satisfies_nacs};
+use crate::transformation::overlap::{overlaps, glue};
+use crate::transformation::util::{check_gluing, image};
+
+/// Why applying the first rule of a critical pair disables the second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConflictKind {
+    /// The first rule deletes an element the second rule matches.
+    DeleteUse,
+    /// The first rule produces an element that completes a NAC of the second.
+    ProduceForbid,
+    /// The first rule relabels a node or edge the second rule matches.
+    ChangeAttribute,
+}
+
+/// A conflict between two rules in a minimal context.
+///
+/// `graph` is the overlap graph on which both rules are applicable, `m1`
+/// and `m2` are the matches L1 → graph and L2 → graph, and applying the
+/// first rule at `m1` destroys the match `m2` of the second.
+#[derive(Debug, Clone)]
+pub struct CriticalPair<N, E> {
+    pub kind: ConflictKind,
+    pub graph: Graph<N, E>,
+    pub m1: Morphism,
+    pub m2: Morphism,
+}
+
+/// Compute the critical pairs in which applying `r1` disables `r2`.
+///
+/// Delete-use and change-attribute conflicts are found on the jointly
+/// surjective overlaps of L1 and L2, produce-forbid conflicts by gluing R1
+/// onto each NAC of `r2` and running `r1` backwards. Only overlaps on which
+/// both rules are applicable (gluing and NACs) are reported. For conflicts
+/// in both directions, call this with the rules swapped as well.
+/// Produce-forbid pairs are not searched for rules that merge nodes.
+///
+/// Set `same_rule` when `r1` and `r2` are the same rule of a rule set, so
+/// the trivial overlap of its LHS with itself is skipped. Two distinct rules
+/// that happen to be equal do conflict on it.
+///
+/// # Errors
+/// Returns the `RuleError` if either rule is malformed.
+pub fn critical_pairs<N, E>(
+    r1: &Rule<N, E>,
+    r2: &Rule<N, E>,
+    same_rule: bool,
+) -> Result<Vec<CriticalPair<N, E>>, RuleError>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    r1.validate()?;
+    r2.validate()?;
+    let mut pairs = Vec::new();
+
+    for o in overlaps(&r1.lhs, &r2.lhs) {
+        if o.node_map.is_empty() || (same_rule && is_identity(r1, &o)) {
+            continue;
+        }
+        let Some(kind) = use_conflict(r1, &o) else {
+            continue;
+        };
+        let (graph, m1, m2) = glue(&r1.lhs, &r2.lhs, &o);
+        if applicable(r1, &graph, &m1) && applicable(r2, &graph, &m2) {
+            pairs.push(CriticalPair { kind, graph, m1, m2 });
+        }
+    }
+
+    let inverse = r1.inverse();
+    let (kept_nodes, kept_edges) = image(&r1.k2r);
+    for nac in &r2.nacs {
+        for o in overlaps(&r1.rhs, &nac.graph) {
+            // Only overlaps on something r1 produces can complete the NAC
+            if o.node_map.keys().all(|n| kept_nodes.contains(n))
+                && o.edge_map.keys().all(|e| kept_edges.contains(e))
+            {
+                continue;
+            }
+            let (glued, r1_match, n2_match) = glue(&r1.rhs, &nac.graph, &o);
+            let Ok(undo) = apply_once(&inverse, &glued, &r1_match) else {
+                continue;
+            };
+            let m2 = undo.tracking.compose(&n2_match.compose(&nac.l2n));
+            if m2.node_map.len() != r2.lhs.node_count() || !embeds(&r2.lhs, &undo.graph, &m2) {
+                continue;
+            }
+            let m1 = undo.comatch;
+            if applicable(r1, &undo.graph, &m1) && applicable(r2, &undo.graph, &m2) {
+                pairs.push(CriticalPair { kind: ConflictKind::ProduceForbid, graph: undo.graph, m1, m2 });
+            }
+        }
+    }
+    Ok(pairs)
+}
+
+/// Classify how `r1` interferes with the elements of L1 in the overlap `o`.
+fn use_conflict<N: Eq, E: Eq>(r1: &Rule<N, E>, o: &Morphism) -> Option<ConflictKind> {
+    let (kept_nodes, kept_edges) = image(&r1.k2l);
+    if o.node_map.keys().any(|n| !kept_nodes.contains(n))
+        || o.edge_map.keys().any(|e| !kept_edges.contains(e))
+    {
+        return Some(ConflictKind::DeleteUse);
+    }
+    let relabels = matches!(r1.merge_policy, MergePolicy::Rhs)
+        && (r1.k2l.node_map.iter().any(|(k, l)| {
+            o.node_map.contains_key(l) && r1.lhs[*l] != r1.rhs[r1.k2r.node_map[k]]
+        }) || r1.k2l.edge_map.iter().any(|(k, l)| {
+            o.edge_map.contains_key(l) && r1.lhs[*l] != r1.rhs[r1.k2r.edge_map[k]]
+        }));
+    relabels.then_some(ConflictKind::ChangeAttribute)
+}
+
+/// Whether `o` identifies every node of a rule's LHS with itself.
+fn is_identity<N, E>(rule: &Rule<N, E>, o: &Morphism) -> bool {
+    rule.lhs.node_indices().all(|n| o.map_node(&n) == Some(&n))
+}
+
+/// Whether `rule` can be applied to `host` at `m` under DPO semantics.
+fn applicable<N, E>(rule: &Rule<N, E>, host: &Graph<N, E>, m: &Morphism) -> bool
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    check_gluing(host, m, rule) && satisfies_nacs(rule, host, m, true)
+}
+
+/// Whether the node map `m` embeds `pattern` into `host` with equal labels.
+fn embeds<N: Eq, E: Eq>(pattern: &Graph<N, E>, host: &Graph<N, E>, m: &Morphism) -> bool {
+    pattern.node_indices().all(|n| {
+        m.map_node(&n).and_then(|h| host.node_weight(*h)) == pattern.node_weight(n)
+    }) && pattern.edge_indices().all(|e| {
+        let (src, dst) = pattern.edge_endpoints(e).unwrap();
+        host.edges_connecting(m.node_map[&src], m.node_map[&dst])
+            .any(|h| *h.weight() == pattern[e])
+    })
+}
@@ -0,0 +1,113 @@
+use petgraph::dot::Dot;
+use petgraph::graph::Graph;
+use std::fmt;
+use crate::analysis::critical_pairs::{critical_pairs, ConflictKind};
+use crate::transformation::{Rule, RuleError};
+
+/// How one rule influences another in a rule dependency graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DependencyKind {
+    /// The source rule produces something the target rule matches.
+    ProduceUse,
+    /// The source rule deletes (or relabels) something the target rule matches.
+    DeleteUse,
+    /// The source rule produces something a NAC of the target rule forbids.
+    ProduceForbid,
+}
+
+impl fmt::Display for DependencyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DependencyKind::ProduceUse => "produce-use",
+            DependencyKind::DeleteUse => "delete-use",
+            DependencyKind::ProduceForbid => "produce-forbid",
+        })
+    }
+}
+
+/// Build the dependency graph of a rule set.
+///
+/// Node `i` carries the index of `rules[i]`. An edge `i -> j` labelled
+/// produce-use means `rules[i]` can enable `rules[j]`; delete-use and
+/// produce-forbid edges mean it can disable it. Change-attribute conflicts
+/// are reported as delete-use, since relabelling removes the matched label.
+///
+/// # Errors
+/// Returns the `RuleError` of the first malformed rule.
+pub fn dependency_graph<N, E>(rules: &[Rule<N, E>]) -> Result<Graph<usize, DependencyKind>, RuleError>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    let mut graph = Graph::with_capacity(rules.len(), 0);
+    let nodes: Vec<_> = (0..rules.len()).map(|i| graph.add_node(i)).collect();
+    for (i, r1) in rules.iter().enumerate() {
+        for (j, r2) in rules.iter().enumerate() {
+            if !r1.dependency_overlaps(r2).is_empty() {
+                graph.add_edge(nodes[i], nodes[j], DependencyKind::ProduceUse);
+            }
+            let pairs = critical_pairs(r1, r2, i == j)?;
+            if pairs.iter().any(|p| p.kind != ConflictKind::ProduceForbid) {
+                graph.add_edge(nodes[i], nodes[j], DependencyKind::DeleteUse);
+            }
+            if pairs.iter().any(|p| p.kind == ConflictKind::ProduceForbid) {
+                graph.add_edge(nodes[i], nodes[j], DependencyKind::ProduceForbid);
+            }
+        }
+    }
+    Ok(graph)
+}
+
+/// Render a dependency graph in Graphviz DOT format.
+pub fn dependency_dot(graph: &Graph<usize, DependencyKind>) -> String {
+    format!("{}", Dot::new(graph))
+}
+
+/// Order rules so that a rule producing what another uses comes first.
+///
+/// This is a topological order of the produce-use edges that keeps the
+/// given order wherever it is free to; rules on a produce-use cycle are
+/// taken in index order.
+///
+/// Dependencies are found by DPO rule composition, so cloning (SqPO) rules
+/// are rejected rather than silently left without dependencies.
+///
+/// # Errors
+/// Returns the `RuleError` of the first malformed rule, and
+/// `RuleError::NotInjective` for a rule whose `k2l` clones a node.
+pub fn application_order<N, E>(rules: &[Rule<N, E>]) -> Result<Vec<usize>, RuleError>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    for rule in rules {
+        rule.validate()?;
+    }
+    let n = rules.len();
+    let mut enables = vec![Vec::new(); n];
+    let mut indegree = vec![0usize; n];
+    for (i, r1) in rules.iter().enumerate() {
+        for (j, r2) in rules.iter().enumerate() {
+            if i != j && !r1.dependency_overlaps(r2).is_empty() {
+                enables[i].push(j);
+                indegree[j] += 1;
+            }
+        }
+    }
+
+    let mut order = Vec::with_capacity(n);
+    let mut done = vec![false; n];
+    while order.len() < n {
+        // Lowest-indexed free rule, or the lowest remaining one to break a cycle
+        let next = (0..n)
+            .find(|&i| !done[i] && indegree[i] == 0)
+            .or_else(|| (0..n).find(|&i| !done[i]))
+            .unwrap();
+        done[next] = true;
+        order.push(next);
+        for &j in &enables[next] {
+            indegree[j] = indegree[j].saturating_sub(1);
+        }
+    }
+    Ok(order)
+}
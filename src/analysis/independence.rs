@@ -0,0 +1,125 @@
+use petgraph::graph::Graph;
+use std::collections::HashSet;
+use crate::transformation::{Rule, Morphism, RewriteError, Derivation};
+use crate::transformation::dpo::{apply_once, satisfies_nacs};
+use crate::transformation::util::check_gluing;
+
+/// Two consecutive rewrite steps G ⇒ H1 ⇒ H2.
+pub type StepPair<N, E> = (Derivation<N, E>, Derivation<N, E>);
+
+/// Check whether two DPO steps on the same host are parallel independent.
+///
+/// The steps `host ⇒ H1` via `rule1` at `m1` and `host ⇒ H2` via `rule2`
+/// at `m2` are independent if each match survives the other step: it is
+/// still total with the same labels, satisfies gluing and every NAC. In
+/// particular neither step deletes or relabels what the other one uses.
+///
+/// # Errors
+/// Returns the `RewriteError` if either step cannot be applied.
+pub fn are_parallel_independent<N, E>(
+    rule1: &Rule<N, E>,
+    m1: &Morphism,
+    rule2: &Rule<N, E>,
+    m2: &Morphism,
+    host: &Graph<N, E>,
+) -> Result<bool, RewriteError>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    let d1 = apply_once(rule1, host, m1)?;
+    let d2 = apply_once(rule2, host, m2)?;
+    Ok(track_match(rule2, host, m2, &d1.tracking, &d1.graph).is_some()
+        && track_match(rule1, host, m1, &d2.tracking, &d2.graph).is_some())
+}
+
+/// Check whether the derivation `host ⇒ H1 ⇒ H2`, applying `rule1` at `m1`
+/// and then `rule2` at `m2` (a match into H1), is sequentially independent.
+///
+/// # Errors
+/// Returns the `RewriteError` if either step cannot be applied.
+pub fn are_sequentially_independent<N, E>(
+    rule1: &Rule<N, E>,
+    m1: &Morphism,
+    rule2: &Rule<N, E>,
+    m2: &Morphism,
+    host: &Graph<N, E>,
+) -> Result<bool, RewriteError>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    Ok(swap_steps(rule1, m1, rule2, m2, host)?.is_some())
+}
+
+/// Swap two sequentially independent steps (Local Church–Rosser).
+///
+/// Given `host ⇒ H1` via `rule1` at `m1` and `H1 ⇒ H2` via `rule2` at
+/// `m2`, returns the steps `host ⇒ H1'` via `rule2` and `H1' ⇒ H2'` via
+/// `rule1`, where H2' is isomorphic to H2. Returns `None` if the steps are
+/// not sequentially independent: `rule2` uses something `rule1` created,
+/// or `rule1` can no longer apply after `rule2` ran first.
+///
+/// # Errors
+/// Returns the `RewriteError` if either of the given steps cannot be applied.
+pub fn swap_steps<N, E>(
+    rule1: &Rule<N, E>,
+    m1: &Morphism,
+    rule2: &Rule<N, E>,
+    m2: &Morphism,
+    host: &Graph<N, E>,
+) -> Result<Option<StepPair<N, E>>, RewriteError>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    let d1 = apply_once(rule1, host, m1)?;
+    apply_once(rule2, &d1.graph, m2)?;
+
+    // Pull the second match back along the first step
+    let Some(m2_first) = track_match(rule2, &d1.graph, m2, &d1.tracking.invert(), host) else {
+        return Ok(None);
+    };
+    let first = apply_once(rule2, host, &m2_first)?;
+    let Some(m1_second) = track_match(rule1, host, m1, &first.tracking, &first.graph) else {
+        return Ok(None);
+    };
+    let second = apply_once(rule1, &first.graph, &m1_second)?;
+    Ok(Some((first, second)))
+}
+
+/// Carry the match `m: L → from` along `tracking: from ⇀ to`.
+///
+/// Returns `None` unless the result is an injective, label-preserving
+/// match into `to` that satisfies gluing and the rule's NACs.
+fn track_match<N, E>(
+    rule: &Rule<N, E>,
+    from: &Graph<N, E>,
+    m: &Morphism,
+    tracking: &Morphism,
+    to: &Graph<N, E>,
+) -> Option<Morphism>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    let mut tracked = Morphism::new();
+    let mut seen = HashSet::new();
+    for l_node in rule.lhs.node_indices() {
+        let t = *tracking.map_node(m.map_node(&l_node)?)?;
+        if to[t] != rule.lhs[l_node] || !seen.insert(t) {
+            return None;
+        }
+        tracked.insert_node(l_node, t);
+    }
+    for l_edge in rule.lhs.edge_indices() {
+        let (l_src, l_dst) = rule.lhs.edge_endpoints(l_edge).unwrap();
+        let h_edge = from.find_edge(m.node_map[&l_src], m.node_map[&l_dst])?;
+        let t = *tracking.map_edge(&h_edge)?;
+        if to[t] != rule.lhs[l_edge] {
+            return None;
+        }
+        tracked.insert_edge(l_edge, t);
+    }
+    (check_gluing(to, &tracked, rule) && satisfies_nacs(rule, to, &tracked, true)).then_some(tracked)
+}
@@ -0,0 +1,17 @@
+pub mod critical_pairs;
+pub mod confluence;
+pub mod dependencies;
+pub mod independence;
+pub mod state_space;
+pub mod temporal;
+pub mod search;
+pub mod termination;
+
+pub use critical_pairs::{critical_pairs, CriticalPair, ConflictKind};
+pub use confluence::{check_local_confluence, join, JoinLimits, Joinability, PairVerdict};
+pub use dependencies::{dependency_graph, dependency_dot, application_order, DependencyKind};
+pub use independence::{are_parallel_independent, are_sequentially_independent, swap_steps, StepPair};
+pub use state_space::{explore, ExploreOptions, SearchOrder, StateSpace, Transition};
+pub use temporal::{check_property, satisfying, Formula, CheckResult};
+pub use search::{Planner, Goal, Plan, Heuristic};
+pub use termination::{prove_termination, Termination, TerminationCertificate};
@@ -0,0 +1,237 @@
+use petgraph::graph::Graph;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+use crate::algorithms::iso::{canonical_hash, find_isomorphism, is_isomorphic};
+use crate::api::has_subgraph;
+use crate::transformation::{Rule, Morphism, RuleError, RewriteError};
+use crate::transformation::dpo::{find_matches, apply_once};
+
+/// A heuristic estimate of the remaining cost from a graph to the goal.
+pub type Heuristic<N, E> = Box<dyn Fn(&Graph<N, E>) -> f64>;
+
+/// What a `Planner` searches for.
+#[derive(Debug, Clone)]
+pub enum Goal<N, E> {
+    /// A graph containing the pattern (checked with edge labels).
+    Pattern(Graph<N, E>),
+    /// A graph isomorphic to the target.
+    Graph(Graph<N, E>),
+}
+
+impl<N, E> Goal<N, E>
+where
+    N: Eq + Clone + Hash,
+    E: Eq + Clone + Hash,
+{
+    /// Whether `graph` reaches the goal. A target graph is compared by
+    /// `canonical_hash` first, so most other graphs are rejected without
+    /// an isomorphism search.
+    pub fn is_reached(&self, graph: &Graph<N, E>) -> bool {
+        match self {
+            Goal::Pattern(p) => has_subgraph(p, graph, true),
+            Goal::Graph(t) => canonical_hash(t) == canonical_hash(graph) && is_isomorphic(t, graph),
+        }
+    }
+}
+
+/// A derivation reaching a goal, with its total rule cost.
+#[derive(Debug, Clone)]
+pub struct Plan<N, E> {
+    /// The applied rules with their matches, each into the graph produced
+    /// by the previous step.
+    pub steps: Vec<(usize, Morphism)>,
+    pub cost: f64,
+    pub graph: Graph<N, E>,
+}
+
+/// A* search for the cheapest derivation reaching a goal.
+///
+/// Each rule has a non-negative cost per application. With the default
+/// zero heuristic this is a uniform-cost search, so with unit costs it
+/// finds a shortest derivation; an admissible heuristic (one that never
+/// overestimates) keeps the result optimal. Graphs are identified up to
+/// isomorphism (bucketed by `canonical_hash`), and ties are broken by discovery order, so searches are
+/// deterministic.
+pub struct Planner<N, E> {
+    rules: Vec<(Rule<N, E>, f64)>,
+    heuristic: Heuristic<N, E>,
+    max_states: usize,
+}
+
+impl<N, E> Planner<N, E>
+where
+    N: Eq + Clone + Hash,
+    E: Eq + Clone + Hash,
+{
+    /// Create a planner without rules, a zero heuristic and a limit of
+    /// 10 000 distinct graphs.
+    pub fn new() -> Self {
+        Planner { rules: Vec::new(), heuristic: Box::new(|_| 0.0), max_states: 10_000 }
+    }
+
+    /// Add a rule with its cost per application.
+    ///
+    /// # Panics
+    /// Panics if `cost` is negative or NaN.
+    pub fn rule(mut self, rule: Rule<N, E>, cost: f64) -> Self {
+        assert!(cost >= 0.0, "rule costs must be non-negative");
+        self.rules.push((rule, cost));
+        self
+    }
+
+    /// Set the heuristic.
+    pub fn heuristic(mut self, heuristic: Heuristic<N, E>) -> Self {
+        self.heuristic = heuristic;
+        self
+    }
+
+    /// Set the maximum number of distinct graphs to generate.
+    pub fn max_states(mut self, max_states: usize) -> Self {
+        self.max_states = max_states;
+        self
+    }
+
+    /// Search from `host` for a derivation reaching `goal`.
+    /// Returns `None` if none exists within the state limit.
+    ///
+    /// # Errors
+    /// Returns the `RuleError` of the first malformed rule.
+    pub fn search(&self, host: &Graph<N, E>, goal: &Goal<N, E>) -> Result<Option<Plan<N, E>>, RuleError> {
+        for (rule, _) in &self.rules {
+            rule.validate()?;
+        }
+        let mut states = vec![Node { graph: host.clone(), cost: 0.0, parent: None }];
+        let mut buckets: HashMap<u64, Vec<usize>> = HashMap::from([(canonical_hash(host), vec![0])]);
+        let mut open = BinaryHeap::from([Entry { f: (self.heuristic)(host), order: 0, state: 0, cost: 0.0 }]);
+        let mut pushed = 1;
+
+        while let Some(entry) = open.pop() {
+            let current = entry.state;
+            if entry.cost > states[current].cost {
+                continue;
+            }
+            if goal.is_reached(&states[current].graph) {
+                return self.replay(host, &states, current).map(Some);
+            }
+            for (i, (rule, step_cost)) in self.rules.iter().enumerate() {
+                for m in find_matches(rule, &states[current].graph, true)? {
+                    let next = match apply_once(rule, &states[current].graph, &m) {
+                        Ok(d) => d.graph,
+                        Err(RewriteError::InvalidRule(e)) => return Err(e),
+                        Err(_) => continue,
+                    };
+                    let cost = states[current].cost + step_cost;
+                    let key = canonical_hash(&next);
+                    let seen = buckets
+                        .get(&key)
+                        .and_then(|b| b.iter().copied().find(|s| is_isomorphic(&states[*s].graph, &next)));
+                    let target = match seen {
+                        Some(s) if states[s].cost <= cost => continue,
+                        Some(s) => {
+                            states[s].cost = cost;
+                            states[s].parent = Some((current, i, m));
+                            s
+                        }
+                        None if states.len() >= self.max_states => continue,
+                        None => {
+                            let f = cost + (self.heuristic)(&next);
+                            states.push(Node { graph: next, cost, parent: Some((current, i, m)) });
+                            buckets.entry(key).or_default().push(states.len() - 1);
+                            open.push(Entry { f, order: pushed, state: states.len() - 1, cost });
+                            pushed += 1;
+                            continue;
+                        }
+                    };
+                    let f = cost + (self.heuristic)(&states[target].graph);
+                    open.push(Entry { f, order: pushed, state: target, cost });
+                    pushed += 1;
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Rebuild the derivation from `host` to `states[last]`, carrying each
+    /// match from the stored representative into the graph actually
+    /// produced by the previous step.
+    fn replay(&self, host: &Graph<N, E>, states: &[Node<N, E>], last: usize) -> Result<Plan<N, E>, RuleError> {
+        let mut path = Vec::new();
+        let mut at = last;
+        while let Some((from, rule, m)) = &states[at].parent {
+            path.push((*from, *rule, m));
+            at = *from;
+        }
+        path.reverse();
+
+        let mut graph = host.clone();
+        let mut steps = Vec::new();
+        for (from, i, m) in path {
+            let rule = &self.rules[i].0;
+            let iso = find_isomorphism(&states[from].graph, &graph).expect("replayed graphs match their states");
+            let mut carried = Morphism::new();
+            for (l, h) in &m.node_map {
+                carried.insert_node(*l, iso[h]);
+            }
+            for l_edge in rule.lhs.edge_indices() {
+                let (src, dst) = rule.lhs.edge_endpoints(l_edge).unwrap();
+                let h_edge = graph.find_edge(carried.node_map[&src], carried.node_map[&dst]).unwrap();
+                carried.insert_edge(l_edge, h_edge);
+            }
+            graph = match apply_once(rule, &graph, &carried) {
+                Ok(d) => d.graph,
+                Err(RewriteError::InvalidRule(e)) => return Err(e),
+                Err(e) => unreachable!("replayed step failed: {e}"),
+            };
+            steps.push((i, carried));
+        }
+        Ok(Plan { steps, cost: states[last].cost, graph })
+    }
+}
+
+impl<N, E> Default for Planner<N, E>
+where
+    N: Eq + Clone + Hash,
+    E: Eq + Clone + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A graph found by the search with its best known cost and the step
+/// that reached it.
+struct Node<N, E> {
+    graph: Graph<N, E>,
+    cost: f64,
+    parent: Option<(usize, usize, Morphism)>,
+}
+
+/// An open-list entry, ordered so the heap pops the lowest `f` first and
+/// the earliest pushed among equal ones.
+struct Entry {
+    f: f64,
+    order: usize,
+    state: usize,
+    cost: f64,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.f.total_cmp(&self.f).then_with(|| other.order.cmp(&self.order))
+    }
+}
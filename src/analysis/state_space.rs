@@ -0,0 +1,160 @@
+use petgraph::graph::{Graph, NodeIndex};
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use crate::algorithms::iso::{canonical_hash, is_isomorphic};
+use crate::transformation::{Rule, Morphism, RuleError, RewriteError};
+use crate::transformation::dpo::{find_matches, apply_once};
+use crate::transformation::util::check_gluing;
+
+/// The order in which states are expanded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchOrder {
+    /// Breadth-first: every state is first reached on a shortest path.
+    #[default]
+    Bfs,
+    /// Depth-first.
+    Dfs,
+}
+
+/// Options for `explore`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExploreOptions {
+    pub order: SearchOrder,
+    /// States discovered at this depth are not expanded.
+    pub max_depth: usize,
+    /// Maximum number of distinct states.
+    pub max_states: usize,
+}
+
+impl Default for ExploreOptions {
+    fn default() -> Self {
+        Self { order: SearchOrder::Bfs, max_depth: 10, max_states: 10_000 }
+    }
+}
+
+/// A transition of the state space: `rules[rule]` applied at `m`, a match
+/// into the source state's graph.
+#[derive(Debug, Clone)]
+pub struct Transition {
+    pub rule: usize,
+    pub m: Morphism,
+}
+
+/// A labelled transition system of graphs reachable by a rule set.
+///
+/// Every node is a state graph, distinct up to isomorphism; every edge is
+/// one rewrite step. A state in `frontier` was cut off by the limits, so
+/// its outgoing transitions are missing or incomplete.
+#[derive(Debug, Clone)]
+pub struct StateSpace<N, E> {
+    pub lts: Graph<Graph<N, E>, Transition>,
+    pub initial: NodeIndex,
+    pub frontier: Vec<NodeIndex>,
+}
+
+impl<N, E> StateSpace<N, E> {
+    /// Whether every reachable state was expanded.
+    pub fn is_complete(&self) -> bool {
+        self.frontier.is_empty()
+    }
+
+    /// Expanded states without outgoing transitions: the normal forms.
+    pub fn terminal_states(&self) -> Vec<NodeIndex> {
+        self.lts
+            .node_indices()
+            .filter(|s| !self.frontier.contains(s) && self.lts.neighbors(*s).next().is_none())
+            .collect()
+    }
+}
+
+/// Explore the graphs reachable from `host` by applying `rules`.
+///
+/// States are identified up to isomorphism: bucketed by `canonical_hash`
+/// and confirmed with `is_isomorphic`, which stops at the first mapping.
+/// Parallel transitions between two states are kept, one per rule and
+/// match. Exploration stops expanding states at `max_depth` (the depth at
+/// which they were first reached) and stops adding states once there are
+/// `max_states`; a state at `max_depth` that no rule applies to still
+/// counts as expanded.
+///
+/// # Errors
+/// Returns the `RuleError` of the first malformed rule.
+pub fn explore<N, E>(
+    host: &Graph<N, E>,
+    rules: &[Rule<N, E>],
+    options: ExploreOptions,
+) -> Result<StateSpace<N, E>, RuleError>
+where
+    N: Eq + Clone + Hash,
+    E: Eq + Clone + Hash,
+{
+    for rule in rules {
+        rule.validate()?;
+    }
+    let mut lts = Graph::new();
+    let mut buckets: HashMap<u64, Vec<NodeIndex>> = HashMap::new();
+    let initial = lts.add_node(host.clone());
+    buckets.entry(canonical_hash(host)).or_default().push(initial);
+
+    let mut frontier = Vec::new();
+    let mut work = VecDeque::from([(initial, 0)]);
+    while let Some((state, depth)) = match options.order {
+        SearchOrder::Bfs => work.pop_front(),
+        SearchOrder::Dfs => work.pop_back(),
+    } {
+        if depth >= options.max_depth {
+            // A state no rule applies to is terminal, not cut off
+            if has_step(rules, &lts[state])? {
+                frontier.push(state);
+            }
+            continue;
+        }
+        let mut truncated = false;
+        for (i, rule) in rules.iter().enumerate() {
+            for m in find_matches(rule, &lts[state], true)? {
+                let next = match apply_once(rule, &lts[state], &m) {
+                    Ok(d) => d.graph,
+                    Err(RewriteError::InvalidRule(e)) => return Err(e),
+                    Err(_) => continue,
+                };
+                let key = canonical_hash(&next);
+                let seen = buckets
+                    .get(&key)
+                    .and_then(|b| b.iter().copied().find(|s| is_isomorphic(&lts[*s], &next)));
+                let target = match seen {
+                    Some(s) => s,
+                    None if lts.node_count() >= options.max_states => {
+                        truncated = true;
+                        continue;
+                    }
+                    None => {
+                        let s = lts.add_node(next);
+                        buckets.entry(key).or_default().push(s);
+                        work.push_back((s, depth + 1));
+                        s
+                    }
+                };
+                lts.add_edge(state, target, Transition { rule: i, m });
+            }
+        }
+        if truncated {
+            frontier.push(state);
+        }
+    }
+    Ok(StateSpace { lts, initial, frontier })
+}
+
+/// Whether some rule has a match into `graph` that satisfies the gluing
+/// condition.
+fn has_step<N, E>(rules: &[Rule<N, E>], graph: &Graph<N, E>) -> Result<bool, RuleError>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    for rule in rules {
+        if find_matches(rule, graph, true)?.iter().any(|m| check_gluing(graph, m, rule)) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
@@ -0,0 +1,308 @@
+use petgraph::graph::{Graph, NodeIndex, EdgeIndex};
+use petgraph::visit::EdgeRef;
+use std::collections::{HashSet, VecDeque};
+use std::ops::Not;
+use crate::api::has_subgraph;
+use crate::analysis::state_space::StateSpace;
+use crate::transformation::Morphism;
+
+/// A CTL formula over the states of a `StateSpace`.
+///
+/// Paths are maximal: they are infinite or end in a state without
+/// transitions. So `AX φ` holds in such a state, `EG φ` holds there if
+/// `φ` does, and `AF φ` does not unless `φ` does.
+#[derive(Debug, Clone)]
+pub enum Formula<N, E> {
+    True,
+    /// The state graph contains the pattern (checked with edge labels).
+    Pattern(Graph<N, E>),
+    /// The state is a normal form: it was expanded and no rule applies.
+    Terminal,
+    Not(Box<Formula<N, E>>),
+    And(Box<Formula<N, E>>, Box<Formula<N, E>>),
+    Or(Box<Formula<N, E>>, Box<Formula<N, E>>),
+    /// Some successor satisfies the formula.
+    Ex(Box<Formula<N, E>>),
+    /// Every successor satisfies the formula.
+    Ax(Box<Formula<N, E>>),
+    /// Some path reaches a state satisfying the formula.
+    Ef(Box<Formula<N, E>>),
+    /// Every path reaches a state satisfying the formula.
+    Af(Box<Formula<N, E>>),
+    /// Some path stays in states satisfying the formula.
+    Eg(Box<Formula<N, E>>),
+    /// Every reachable state satisfies the formula.
+    Ag(Box<Formula<N, E>>),
+    /// Some path satisfies the first formula until the second holds.
+    Eu(Box<Formula<N, E>>, Box<Formula<N, E>>),
+    /// Every path satisfies the first formula until the second holds.
+    Au(Box<Formula<N, E>>, Box<Formula<N, E>>),
+}
+
+impl<N, E> Formula<N, E> {
+    /// The atomic proposition "contains `pattern`".
+    pub fn pattern(pattern: Graph<N, E>) -> Self {
+        Formula::Pattern(pattern)
+    }
+
+    pub fn and(self, other: Formula<N, E>) -> Self {
+        Formula::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: Formula<N, E>) -> Self {
+        Formula::Or(Box::new(self), Box::new(other))
+    }
+
+    pub fn ex(self) -> Self {
+        Formula::Ex(Box::new(self))
+    }
+
+    pub fn ax(self) -> Self {
+        Formula::Ax(Box::new(self))
+    }
+
+    pub fn ef(self) -> Self {
+        Formula::Ef(Box::new(self))
+    }
+
+    pub fn af(self) -> Self {
+        Formula::Af(Box::new(self))
+    }
+
+    pub fn eg(self) -> Self {
+        Formula::Eg(Box::new(self))
+    }
+
+    pub fn ag(self) -> Self {
+        Formula::Ag(Box::new(self))
+    }
+
+    /// `E[self U until]`
+    pub fn eu(self, until: Formula<N, E>) -> Self {
+        Formula::Eu(Box::new(self), Box::new(until))
+    }
+
+    /// `A[self U until]`
+    pub fn au(self, until: Formula<N, E>) -> Self {
+        Formula::Au(Box::new(self), Box::new(until))
+    }
+}
+
+impl<N, E> Not for Formula<N, E> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Formula::Not(Box::new(self))
+    }
+}
+
+/// The verdict of `check_property` in the initial state.
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub holds: bool,
+    /// False if the state space was cut off by its limits, in which case
+    /// the verdict only covers the explored part.
+    pub complete: bool,
+    /// A derivation from the initial state as `(rule, match)` steps: a
+    /// witness for `EX`, `EF`, `EG` and `EU` formulas that hold, a
+    /// counterexample for `AX`, `AF`, `AG` and `AU` formulas that fail,
+    /// empty otherwise. Paths into a cycle end on the first repeated state.
+    pub trace: Vec<(usize, Morphism)>,
+}
+
+/// Check `formula` in the initial state of `space`.
+pub fn check_property<N, E>(space: &StateSpace<N, E>, formula: &Formula<N, E>) -> CheckResult
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    let s0 = space.initial;
+    let holds = satisfying(space, formula)[s0.index()];
+    let everywhere = vec![true; space.lts.node_count()];
+    let edges = match (holds, formula) {
+        (true, Formula::Ex(a)) => first_step(space, s0, &satisfying(space, a)),
+        (false, Formula::Ax(a)) => first_step(space, s0, &negate(satisfying(space, a))),
+        (true, Formula::Ef(a)) => path_to(space, s0, &everywhere, &satisfying(space, a)),
+        (true, Formula::Eu(a, b)) => path_to(space, s0, &satisfying(space, a), &satisfying(space, b)),
+        (false, Formula::Ag(a)) => path_to(space, s0, &everywhere, &negate(satisfying(space, a))),
+        (true, Formula::Eg(_)) | (false, Formula::Af(_)) | (false, Formula::Au(_, _)) => {
+            // Stay in the states where the formula (resp. its failure) holds
+            let sat = satisfying(space, formula);
+            follow(space, s0, &if holds { sat } else { negate(sat) })
+        }
+        _ => Vec::new(),
+    };
+    CheckResult {
+        holds,
+        complete: space.is_complete(),
+        trace: edges
+            .into_iter()
+            .map(|e| (space.lts[e].rule, space.lts[e].m.clone()))
+            .collect(),
+    }
+}
+
+/// The states of `space` satisfying `formula`, indexed by state.
+pub fn satisfying<N, E>(space: &StateSpace<N, E>, formula: &Formula<N, E>) -> Vec<bool>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    let lts = &space.lts;
+    let states: Vec<NodeIndex> = lts.node_indices().collect();
+    match formula {
+        Formula::True => vec![true; states.len()],
+        Formula::Pattern(p) => states.iter().map(|s| has_subgraph(p, &lts[*s], true)).collect(),
+        Formula::Terminal => {
+            let terminal = space.terminal_states();
+            states.iter().map(|s| terminal.contains(s)).collect()
+        }
+        Formula::Not(a) => negate(satisfying(space, a)),
+        Formula::And(a, b) => zip(satisfying(space, a), satisfying(space, b), |x, y| x && y),
+        Formula::Or(a, b) => zip(satisfying(space, a), satisfying(space, b), |x, y| x || y),
+        Formula::Ex(a) => {
+            let a = satisfying(space, a);
+            states.iter().map(|s| lts.neighbors(*s).any(|t| a[t.index()])).collect()
+        }
+        Formula::Ax(a) => {
+            let a = satisfying(space, a);
+            states.iter().map(|s| lts.neighbors(*s).all(|t| a[t.index()])).collect()
+        }
+        Formula::Ef(a) => until(space, vec![true; states.len()], satisfying(space, a), false),
+        Formula::Af(a) => until(space, vec![true; states.len()], satisfying(space, a), true),
+        Formula::Eu(a, b) => until(space, satisfying(space, a), satisfying(space, b), false),
+        Formula::Au(a, b) => until(space, satisfying(space, a), satisfying(space, b), true),
+        Formula::Eg(a) => globally(space, satisfying(space, a), false),
+        Formula::Ag(a) => globally(space, satisfying(space, a), true),
+    }
+}
+
+/// Least fixpoint of `x = b ∨ (a ∧ EX x)`, or `x = b ∨ (a ∧ AX x)` with at
+/// least one successor if `all`.
+fn until<N, E>(space: &StateSpace<N, E>, a: Vec<bool>, b: Vec<bool>, all: bool) -> Vec<bool> {
+    let lts = &space.lts;
+    let mut x = b;
+    loop {
+        let mut changed = false;
+        for s in lts.node_indices() {
+            if x[s.index()] || !a[s.index()] {
+                continue;
+            }
+            let mut next = lts.neighbors(s).peekable();
+            let step = if all {
+                next.peek().is_some() && next.all(|t| x[t.index()])
+            } else {
+                next.any(|t| x[t.index()])
+            };
+            if step {
+                x[s.index()] = true;
+                changed = true;
+            }
+        }
+        if !changed {
+            return x;
+        }
+    }
+}
+
+/// Greatest fixpoint of `x = a ∧ AX x`, or `x = a ∧ (EX x ∨ no successor)`
+/// if not `all`.
+fn globally<N, E>(space: &StateSpace<N, E>, a: Vec<bool>, all: bool) -> Vec<bool> {
+    let lts = &space.lts;
+    let mut x = a;
+    loop {
+        let mut changed = false;
+        for s in lts.node_indices() {
+            if !x[s.index()] {
+                continue;
+            }
+            let mut next = lts.neighbors(s).peekable();
+            let keep = if all {
+                next.all(|t| x[t.index()])
+            } else {
+                next.peek().is_none() || next.any(|t| x[t.index()])
+            };
+            if !keep {
+                x[s.index()] = false;
+                changed = true;
+            }
+        }
+        if !changed {
+            return x;
+        }
+    }
+}
+
+/// The first transition from `s` into a `goal` state, as a one-step path.
+fn first_step<N, E>(space: &StateSpace<N, E>, s: NodeIndex, goal: &[bool]) -> Vec<EdgeIndex> {
+    space
+        .lts
+        .edges(s)
+        .filter(|e| goal[e.target().index()])
+        .map(|e| e.id())
+        .min()
+        .into_iter()
+        .collect()
+}
+
+/// The transitions out of `s` in the order they were added.
+fn successors<N, E>(space: &StateSpace<N, E>, s: NodeIndex) -> Vec<(EdgeIndex, NodeIndex)> {
+    let mut out: Vec<_> = space.lts.edges(s).map(|e| (e.id(), e.target())).collect();
+    out.sort_unstable();
+    out
+}
+
+/// A shortest path from `s` through `allowed` states into a `goal` state.
+fn path_to<N, E>(space: &StateSpace<N, E>, s: NodeIndex, allowed: &[bool], goal: &[bool]) -> Vec<EdgeIndex> {
+    let lts = &space.lts;
+    let mut parent: Vec<Option<EdgeIndex>> = vec![None; lts.node_count()];
+    let mut seen = HashSet::from([s]);
+    let mut queue = VecDeque::from([s]);
+    while let Some(u) = queue.pop_front() {
+        if goal[u.index()] {
+            let mut path = Vec::new();
+            let mut at = u;
+            while let Some(e) = parent[at.index()] {
+                path.push(e);
+                at = lts.edge_endpoints(e).unwrap().0;
+            }
+            path.reverse();
+            return path;
+        }
+        if !allowed[u.index()] {
+            continue;
+        }
+        for (e, v) in successors(space, u) {
+            if seen.insert(v) {
+                parent[v.index()] = Some(e);
+                queue.push_back(v);
+            }
+        }
+    }
+    Vec::new()
+}
+
+/// Walk from `s` through `stay` states, taking the first such successor,
+/// until there is none or a state repeats.
+fn follow<N, E>(space: &StateSpace<N, E>, s: NodeIndex, stay: &[bool]) -> Vec<EdgeIndex> {
+    let lts = &space.lts;
+    let mut path = Vec::new();
+    let mut seen = HashSet::from([s]);
+    let mut at = s;
+    while let Some(e) = first_step(space, at, stay).pop() {
+        path.push(e);
+        at = lts.edge_endpoints(e).unwrap().1;
+        if !seen.insert(at) {
+            break;
+        }
+    }
+    path
+}
+
+fn negate(x: Vec<bool>) -> Vec<bool> {
+    x.into_iter().map(|b| !b).collect()
+}
+
+fn zip(x: Vec<bool>, y: Vec<bool>, f: impl Fn(bool, bool) -> bool) -> Vec<bool> {
+    x.into_iter().zip(y).map(|(a, b)| f(a, b)).collect()
+}
@@ -0,0 +1,279 @@
+use petgraph::graph::Graph;
+use std::cmp::Ordering;
+use std::ops::{Div, Mul, Sub};
+use crate::transformation::{Rule, MergePolicy, RuleError};
+
+/// Non-negative integer weights for node and edge labels under which every
+/// rule of a rule set strictly decreases the total weight of the host.
+///
+/// Labels that are not listed weigh zero. Since the total weight of a graph
+/// is a natural number, no derivation can be longer than the weight of its
+/// start graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerminationCertificate<N, E> {
+    pub node_weights: Vec<(N, u64)>,
+    pub edge_weights: Vec<(E, u64)>,
+}
+
+impl<N: Eq, E: Eq> TerminationCertificate<N, E> {
+    /// The total weight of `g`.
+    pub fn weight(&self, g: &Graph<N, E>) -> u64 {
+        let node = |l: &N| self.node_weights.iter().find(|(k, _)| k == l).map_or(0, |(_, w)| *w);
+        let edge = |l: &E| self.edge_weights.iter().find(|(k, _)| k == l).map_or(0, |(_, w)| *w);
+        g.node_weights().map(node).sum::<u64>() + g.edge_weights().map(edge).sum::<u64>()
+    }
+}
+
+/// The outcome of `prove_termination`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Termination<N, E> {
+    /// The rule set terminates on every host graph.
+    Terminates(TerminationCertificate<N, E>),
+    /// No weight function exists, or a rule's effect on labels is unknown.
+    Unknown,
+}
+
+/// Try to prove that a DPO rule set terminates with a weight function.
+///
+/// Each rule changes the number of host elements of every label by a fixed
+/// amount (RHS count minus LHS count, taking the merge policy into account:
+/// preserved nodes and edges keep their LHS label under `Survivor`),
+/// so a weighting of labels under which every rule has a negative effect is
+/// the solution of a small linear system, found here by exact simplex.
+/// Rules with `MergePolicy::Combine` relabel unpredictably and give
+/// `Termination::Unknown`. NACs are ignored, which is sound but may miss
+/// proofs that depend on them.
+///
+/// # Errors
+/// Returns the `RuleError` of the first malformed rule.
+pub fn prove_termination<N, E>(rules: &[Rule<N, E>]) -> Result<Termination<N, E>, RuleError>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    let mut node_labels: Vec<N> = Vec::new();
+    let mut edge_labels: Vec<E> = Vec::new();
+    for rule in rules {
+        rule.validate()?;
+        if matches!(rule.merge_policy, MergePolicy::Combine(_)) {
+            return Ok(Termination::Unknown);
+        }
+        for g in [&rule.lhs, &rule.rhs] {
+            g.node_weights().for_each(|l| insert_label(&mut node_labels, l));
+            g.edge_weights().for_each(|l| insert_label(&mut edge_labels, l));
+        }
+    }
+
+    // One row per rule: the change in the count of every node label, then
+    // of every edge label
+    let n = node_labels.len() + edge_labels.len();
+    let node = |l: &N| node_labels.iter().position(|k| k == l).unwrap();
+    let edge = |l: &E| node_labels.len() + edge_labels.iter().position(|k| k == l).unwrap();
+    let matrix: Vec<Vec<i64>> = rules
+        .iter()
+        .map(|rule| {
+            let mut row = vec![0; n];
+            for l in rule.lhs.node_indices() {
+                row[node(&rule.lhs[l])] -= 1;
+            }
+            for r in rule.rhs.node_indices() {
+                // A preserved node keeps its matched label under `Survivor`
+                let kept = rule
+                    .k2r
+                    .node_map
+                    .iter()
+                    .filter(|(_, v)| **v == r)
+                    .min_by_key(|(k, _)| **k)
+                    .map(|(k, _)| rule.k2l.node_map[k]);
+                let label = match kept {
+                    Some(l) if matches!(rule.merge_policy, MergePolicy::Survivor) => &rule.lhs[l],
+                    _ => &rule.rhs[r],
+                };
+                row[node(label)] += 1;
+            }
+            for e in rule.lhs.edge_weights() {
+                row[edge(e)] -= 1;
+            }
+            for r in rule.rhs.edge_indices() {
+                // Likewise a preserved edge keeps its matched label
+                let kept = rule
+                    .k2r
+                    .edge_map
+                    .iter()
+                    .filter(|(_, v)| **v == r)
+                    .min_by_key(|(k, _)| **k)
+                    .map(|(k, _)| rule.k2l.edge_map[k]);
+                let label = match kept {
+                    Some(l) if matches!(rule.merge_policy, MergePolicy::Survivor) => &rule.lhs[l],
+                    _ => &rule.rhs[r],
+                };
+                row[edge(label)] += 1;
+            }
+            row
+        })
+        .collect();
+
+    let Some(weights) = solve(&matrix, n) else {
+        return Ok(Termination::Unknown);
+    };
+    let (nodes, edges) = weights.split_at(node_labels.len());
+    Ok(Termination::Terminates(TerminationCertificate {
+        node_weights: node_labels.into_iter().zip(nodes.iter().copied()).filter(|(_, w)| *w > 0).collect(),
+        edge_weights: edge_labels.into_iter().zip(edges.iter().copied()).filter(|(_, w)| *w > 0).collect(),
+    }))
+}
+
+/// Append `label` to `labels` unless it is already there.
+fn insert_label<T: Eq + Clone>(labels: &mut Vec<T>, label: &T) {
+    if !labels.contains(label) {
+        labels.push(label.clone());
+    }
+}
+
+/// Find non-negative integers `w` with `row · w <= -1` for every row.
+///
+/// Maximises `t` subject to `row · w + t <= 0`, `w <= 1` and `t <= 1`,
+/// whose origin is feasible, with Bland's rule. A positive optimum gives
+/// the rational solution `w / t`, scaled to the smallest integers.
+fn solve(matrix: &[Vec<i64>], n: usize) -> Option<Vec<u64>> {
+    let m = matrix.len();
+    let vars = n + 1;
+    let rows = m + vars;
+    let width = vars + rows + 1;
+    let zero = Ratio::int(0);
+    let one = Ratio::int(1);
+
+    let mut tableau = vec![vec![zero; width]; rows + 1];
+    for (i, row) in matrix.iter().enumerate() {
+        for (j, d) in row.iter().enumerate() {
+            tableau[i][j] = Ratio::int(*d);
+        }
+        tableau[i][n] = one;
+    }
+    for j in 0..vars {
+        tableau[m + j][j] = one;
+        tableau[m + j][width - 1] = one;
+    }
+    for (i, row) in tableau.iter_mut().take(rows).enumerate() {
+        row[vars + i] = one;
+    }
+    // Objective row: z - t = 0
+    tableau[rows][n] = Ratio::int(-1);
+    let mut basis: Vec<usize> = (vars..vars + rows).collect();
+
+    while let Some(enter) = (0..width - 1).find(|&j| tableau[rows][j].is_negative()) {
+        let leave = (0..rows)
+            .filter(|&i| tableau[i][enter].is_positive())
+            .min_by(|&a, &b| {
+                let ra = tableau[a][width - 1] / tableau[a][enter];
+                let rb = tableau[b][width - 1] / tableau[b][enter];
+                ra.cmp(&rb).then(basis[a].cmp(&basis[b]))
+            })?;
+        let pivot = tableau[leave][enter];
+        for x in tableau[leave].iter_mut() {
+            *x = *x / pivot;
+        }
+        let pivot_row = tableau[leave].clone();
+        for (i, row) in tableau.iter_mut().enumerate() {
+            let factor = row[enter];
+            if i != leave && !factor.is_zero() {
+                for (x, p) in row.iter_mut().zip(&pivot_row) {
+                    *x = *x - factor * *p;
+                }
+            }
+        }
+        basis[leave] = enter;
+    }
+
+    let value = |j: usize| basis.iter().position(|b| *b == j).map_or(zero, |i| tableau[i][width - 1]);
+    let t = value(n);
+    if !t.is_positive() {
+        return None;
+    }
+    let weights: Vec<Ratio> = (0..n).map(|j| value(j) / t).collect();
+    let scale = weights.iter().fold(1, |acc, w| lcm(acc, w.den));
+    let mut integers: Vec<u64> = weights.iter().map(|w| (w.num * (scale / w.den)) as u64).collect();
+    let common = integers.iter().fold(0, |acc, w| gcd(acc, *w as i128)) as u64;
+    if common > 1 {
+        integers.iter_mut().for_each(|w| *w /= common);
+    }
+    Some(integers)
+}
+
+/// An exact rational number in lowest terms with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Ratio {
+    num: i128,
+    den: i128,
+}
+
+impl Ratio {
+    fn new(num: i128, den: i128) -> Self {
+        let g = gcd(num, den).max(1) * den.signum();
+        Ratio { num: num / g, den: den / g }
+    }
+
+    fn int(n: i64) -> Self {
+        Ratio { num: n as i128, den: 1 }
+    }
+
+    fn is_zero(self) -> bool {
+        self.num == 0
+    }
+
+    fn is_positive(self) -> bool {
+        self.num > 0
+    }
+
+    fn is_negative(self) -> bool {
+        self.num < 0
+    }
+}
+
+impl Sub for Ratio {
+    type Output = Ratio;
+
+    fn sub(self, other: Ratio) -> Ratio {
+        Ratio::new(self.num * other.den - other.num * self.den, self.den * other.den)
+    }
+}
+
+impl Mul for Ratio {
+    type Output = Ratio;
+
+    fn mul(self, other: Ratio) -> Ratio {
+        Ratio::new(self.num * other.num, self.den * other.den)
+    }
+}
+
+impl Div for Ratio {
+    type Output = Ratio;
+
+    fn div(self, other: Ratio) -> Ratio {
+        Ratio::new(self.num * other.den, self.den * other.num)
+    }
+}
+
+impl Ord for Ratio {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn lcm(a: i128, b: i128) -> i128 {
+    a / gcd(a, b) * b
+}
@@ -1,15 +1,21 @@
 // src/api.rs
 
-use petgraph::graph::{Graph, NodeIndex};
-use std::collections::HashMap;
+use petgraph::graph::{Graph, NodeIndex, EdgeIndex};
+use std::collections::{HashMap, HashSet};
 
 use crate::algorithms::vf2::{find_mappings as vf2_find_mappings, vf2_subgraph_isomorphism};
+use crate::algorithms::iso::{canonical_hash, is_isomorphic};
 use crate::transformation::{
-    find_matches as dpo_find_matches, apply_once, apply, Rule, Morphism,
+    find_matches as dpo_find_matches, apply_once, apply_once_mut, Rule, Morphism, RuleSchema,
+    MergePolicy, Nac, RuleError, RewriteError, Comatch,
 };
+use crate::transformation::spo;
+use crate::transformation::util::check_gluing;
+use crate::analysis::{application_order, are_parallel_independent};
 use crate::io::neo4j::{graph_from_neo4j_json, NodeData, Neo4jError};
 use crate::transformation::rule::write_cypher_to_file;
 use std::fmt::Display;
+use std::hash::Hash;
 use std::path::Path;
 /// Options for VF2‐based subgraph matching.
 #[derive(Debug, Clone)]
@@ -58,10 +64,55 @@ where
     vf2_subgraph_isomorphism(pattern, host, check_edge_labels)
 }
 
+/// Which rewriting semantics to use when applying a rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RewriteMode {
+    /// Double pushout: refuse to rewrite if deletion would leave dangling edges.
+    #[default]
+    Dpo,
+    /// Single pushout: delete dangling edges together with their nodes.
+    Spo,
+}
+
+/// Rewrite `host` in place with `rule` at match `m` under the given semantics.
+fn rewrite_at<N, E>(
+    rule: &Rule<N, E>,
+    host: &mut Graph<N, E>,
+    m: &Morphism,
+    mode: RewriteMode,
+) -> Result<Comatch, RewriteError>
+where
+    N: Clone,
+    E: Clone,
+{
+    match mode {
+        RewriteMode::Dpo => apply_once_mut(host, rule, m),
+        RewriteMode::Spo => spo::apply_once_mut(host, rule, m),
+    }
+}
+
 /// A trait for “apply‐once” graph‐rewrites.
-pub trait GraphRewrite<N, E> {
-    /// Try to apply this rewrite to `host`.
-    fn apply(&self, host: &Graph<N, E>) -> Option<Graph<N, E>>;
+pub trait GraphRewrite<N: Clone, E: Clone> {
+    /// Try to apply this rewrite to `host` in place under the given semantics.
+    /// Returns the comatch; `host` is left untouched on failure.
+    fn apply_mut_with(&self, host: &mut Graph<N, E>, mode: RewriteMode) -> Result<Comatch, RewriteError>;
+
+    /// Try to apply this rewrite to `host` in place with DPO semantics.
+    fn apply_mut(&self, host: &mut Graph<N, E>) -> Result<Comatch, RewriteError> {
+        self.apply_mut_with(host, RewriteMode::Dpo)
+    }
+
+    /// Try to apply this rewrite to a copy of `host` under the given semantics.
+    fn apply_with(&self, host: &Graph<N, E>, mode: RewriteMode) -> Result<Graph<N, E>, RewriteError> {
+        let mut result = host.clone();
+        self.apply_mut_with(&mut result, mode)?;
+        Ok(result)
+    }
+
+    /// Try to apply this rewrite to a copy of `host` with DPO semantics.
+    fn apply(&self, host: &Graph<N, E>) -> Result<Graph<N, E>, RewriteError> {
+        self.apply_with(host, RewriteMode::Dpo)
+    }
 }
 
 impl<N, E> GraphRewrite<N, E> for Rule<N, E>
@@ -69,20 +120,39 @@ where
     N: Eq + Clone,
     E: Eq + Clone,
 {
-    fn apply(&self, host: &Graph<N, E>) -> Option<Graph<N, E>> {
-        if let Some(m) = dpo_find_matches(self, host, true).into_iter().next() {
-            apply_once(self, host, &m)
+    fn apply_mut_with(&self, host: &mut Graph<N, E>, mode: RewriteMode) -> Result<Comatch, RewriteError> {
+        if let Some(m) = dpo_find_matches(self, host, true)?.into_iter().next() {
+            rewrite_at(self, host, &m, mode)
         } else {
-            None
+            Err(RewriteError::NoMatch)
         }
     }
 }
 
-/// Apply a single DPO rule once (returns `None` if no match or gluing fails).
+impl<N, E> GraphRewrite<N, E> for RuleSchema<N, E>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    fn apply_mut_with(&self, host: &mut Graph<N, E>, mode: RewriteMode) -> Result<Comatch, RewriteError> {
+        let (m, binding) = self
+            .find_matches(host, true)?
+            .into_iter()
+            .next()
+            .ok_or(RewriteError::NoMatch)?;
+        rewrite_at(&self.instantiate(&binding)?, host, &m, mode)
+    }
+}
+
+/// Apply a single DPO rule once.
+///
+/// # Errors
+/// Returns `RewriteError::NoMatch` if the rule does not match, or the error
+/// of the underlying rewrite (invalid rule, gluing failure).
 pub fn apply_rule<N, E>(
     host: &Graph<N, E>,
     rule: &Rule<N, E>,
-) -> Option<Graph<N, E>>
+) -> Result<Graph<N, E>, RewriteError>
 where
     N: Eq + Clone,
     E: Eq + Clone,
@@ -90,36 +160,347 @@ where
     rule.apply(host)
 }
 
+/// Apply a single rule once under the chosen semantics.
+///
+/// # Errors
+/// Same as `apply_rule`; gluing failures only occur in DPO mode.
+pub fn apply_rule_with<N, E>(
+    host: &Graph<N, E>,
+    rule: &Rule<N, E>,
+    mode: RewriteMode,
+) -> Result<Graph<N, E>, RewriteError>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    rule.apply_with(host, mode)
+}
+
 /// Sequentially apply a list of rules, threading the graph forward.
 /// Each rule is applied at most once (if it matches).
+///
+/// Rules are taken in `application_order`, so a rule that produces what
+/// another one uses is tried first; otherwise the given order is kept.
+/// Computing that order analyses every pair of rules, so callers applying
+/// the same rules repeatedly should compute it once and use
+/// `apply_rules_in_order`.
+///
+/// # Errors
+/// Returns the `RuleError` of the first malformed rule.
 pub fn apply_rules<N, E>(
     host: &Graph<N, E>,
     rules: &[Rule<N, E>],
-) -> Graph<N, E>
+) -> Result<Graph<N, E>, RuleError>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    apply_rules_in_order(host, rules, &application_order(rules)?)
+}
+
+/// Apply `rules[i]` for each `i` of `order` in turn, at most once each (if
+/// it matches), threading the graph forward.
+///
+/// # Errors
+/// Returns the `RuleError` of the first malformed rule.
+///
+/// # Panics
+/// Panics if an index in `order` is out of bounds.
+pub fn apply_rules_in_order<N, E>(
+    host: &Graph<N, E>,
+    rules: &[Rule<N, E>],
+    order: &[usize],
+) -> Result<Graph<N, E>, RuleError>
 where
     N: Eq + Clone,
     E: Eq + Clone,
 {
     let mut current = host.clone();
-    for rule in rules {
-        if let Some(next) = apply_rule(&current, rule) {
-            current = next;
+    for rule in order.iter().map(|&i| &rules[i]) {
+        // A rule that does not match or fails gluing leaves the graph untouched
+        if let Err(RewriteError::InvalidRule(e)) = rule.apply_mut(&mut current) {
+            return Err(e);
         }
     }
-    current
+    Ok(current)
 }
 
-/// Exhaustively apply a single rule (all rewrite branches).
-/// Returns **all** resulting graphs where no further match exists.
-pub fn apply_exhaustive<N, E>(
+/// Apply `rule` at a maximal set of pairwise parallel independent matches
+/// simultaneously, as one DPO step with the parallel rule `rule + … + rule`.
+///
+/// Matches are considered in the order `find_matches` returns them and
+/// taken greedily, so the set is maximal but not necessarily maximum.
+/// Independence is read off the match images: a match is taken if it
+/// satisfies gluing and neither it nor any match taken so far uses a host
+/// element the other one deletes, merges or relabels. For a rule with NACs,
+/// `are_parallel_independent` must also hold, since a step may create what
+/// a NAC of the other forbids.
+///
+/// # Errors
+/// Returns `RewriteError::NoMatch` if the rule is not applicable anywhere,
+/// or `RewriteError::InvalidRule` if it is malformed.
+pub fn apply_parallel<N, E>(
     host: &Graph<N, E>,
     rule: &Rule<N, E>,
-) -> Vec<Graph<N, E>>
+) -> Result<Graph<N, E>, RewriteError>
 where
     N: Eq + Clone,
     E: Eq + Clone,
 {
-    apply(rule, host, true)
+    rule.validate()?;
+    let (changed_nodes, changed_edges) = changed_elements(rule);
+    let mut chosen: Vec<(Morphism, Footprint)> = Vec::new();
+    for m in dpo_find_matches(rule, host, true)? {
+        if !check_gluing(host, &m, rule) {
+            continue;
+        }
+        let footprint = Footprint::new(rule, &m, host, &changed_nodes, &changed_edges);
+        let mut independent = chosen.iter().all(|(_, c)| c.is_independent_of(&footprint));
+        if independent && !rule.nacs.is_empty() {
+            for (c, _) in &chosen {
+                if !are_parallel_independent(rule, c, rule, &m, host)? {
+                    independent = false;
+                    break;
+                }
+            }
+        }
+        if independent {
+            chosen.push((m, footprint));
+        }
+    }
+    if chosen.is_empty() {
+        return Err(RewriteError::NoMatch);
+    }
+
+    // Copy i of the rule sits at offset i * |L| in the parallel rule. Its
+    // NACs were checked per match above and are not checked when rewriting.
+    let mut single = rule.clone();
+    single.nacs.clear();
+    let parallel = single.parallel_copies(chosen.len());
+    let mut combined = Morphism::new();
+    for (i, (m, _)) in chosen.iter().enumerate() {
+        let offset = i * rule.lhs.node_count();
+        for (l, h) in &m.node_map {
+            combined.insert_node(NodeIndex::new(l.index() + offset), *h);
+        }
+    }
+    let mut result = host.clone();
+    apply_once_mut(&mut result, &parallel, &combined)?;
+    Ok(result)
+}
+
+/// The LHS nodes and edges that `rule` deletes, merges or relabels.
+fn changed_elements<N: Eq, E: Eq>(rule: &Rule<N, E>) -> (Vec<NodeIndex>, Vec<EdgeIndex>) {
+    let fused_nodes = |r: &NodeIndex| rule.k2r.node_map.values().filter(|v| *v == r).count() > 1;
+    let fused_edges = |r: &EdgeIndex| rule.k2r.edge_map.values().filter(|v| *v == r).count() > 1;
+    let nodes = rule
+        .lhs
+        .node_indices()
+        .filter(|l| {
+            let mut preimages = rule.k2l.node_map.iter().filter(|(_, v)| *v == l).map(|(k, _)| k);
+            match preimages.next().and_then(|k| rule.k2r.map_node(k)) {
+                Some(r) => {
+                    fused_nodes(r)
+                        || (matches!(rule.merge_policy, MergePolicy::Rhs) && rule.rhs[*r] != rule.lhs[*l])
+                }
+                None => true,
+            }
+        })
+        .collect();
+    let edges = rule
+        .lhs
+        .edge_indices()
+        .filter(|l| {
+            let preimage = rule.k2l.edge_map.iter().find(|(_, v)| *v == l).map(|(k, _)| k);
+            match preimage.and_then(|k| rule.k2r.map_edge(k)) {
+                Some(r) => {
+                    fused_edges(r)
+                        || (matches!(rule.merge_policy, MergePolicy::Rhs) && rule.rhs[*r] != rule.lhs[*l])
+                }
+                None => true,
+            }
+        })
+        .collect();
+    (nodes, edges)
+}
+
+/// The host elements a match uses and those its step changes.
+struct Footprint {
+    used_nodes: HashSet<NodeIndex>,
+    used_edges: HashSet<EdgeIndex>,
+    changed_nodes: HashSet<NodeIndex>,
+    changed_edges: HashSet<EdgeIndex>,
+}
+
+impl Footprint {
+    fn new<N, E>(
+        rule: &Rule<N, E>,
+        m: &Morphism,
+        host: &Graph<N, E>,
+        changed_nodes: &[NodeIndex],
+        changed_edges: &[EdgeIndex],
+    ) -> Self {
+        // Host edges are found from their endpoints, as when rewriting
+        let edge_image = |l: EdgeIndex| {
+            let (src, dst) = rule.lhs.edge_endpoints(l).unwrap();
+            host.find_edge(m.node_map[&src], m.node_map[&dst])
+        };
+        Footprint {
+            used_nodes: m.node_map.values().copied().collect(),
+            used_edges: rule.lhs.edge_indices().filter_map(edge_image).collect(),
+            changed_nodes: changed_nodes.iter().map(|l| m.node_map[l]).collect(),
+            changed_edges: changed_edges.iter().filter_map(|l| edge_image(*l)).collect(),
+        }
+    }
+
+    /// Whether neither step changes anything the other one uses.
+    fn is_independent_of(&self, other: &Footprint) -> bool {
+        self.changed_nodes.is_disjoint(&other.used_nodes)
+            && self.changed_edges.is_disjoint(&other.used_edges)
+            && other.changed_nodes.is_disjoint(&self.used_nodes)
+            && other.changed_edges.is_disjoint(&self.used_edges)
+    }
+}
+
+/// Limits for `apply_exhaustive`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExhaustiveLimits {
+    /// Maximum number of rewrite steps over all branches.
+    pub max_steps: usize,
+    /// Maximum number of distinct graphs (up to isomorphism) visited.
+    pub max_branches: usize,
+}
+
+impl Default for ExhaustiveLimits {
+    fn default() -> Self {
+        Self { max_steps: 10_000, max_branches: 1_000 }
+    }
+}
+
+/// Why `apply_exhaustive` stopped before finding every normal form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StopReason {
+    /// The rule is trivially non-terminating and matches the host.
+    NonTerminatingRule,
+    /// Some branch rewrites a graph back into itself (up to isomorphism).
+    Cycle,
+    /// `max_steps` was reached.
+    MaxSteps,
+    /// `max_branches` was reached.
+    MaxBranches,
+}
+
+/// The outcome of `apply_exhaustive`.
+#[derive(Debug, Clone)]
+pub enum Exhaustive<N, E> {
+    /// Every branch terminated; these are all normal forms up to isomorphism.
+    NormalForms(Vec<Graph<N, E>>),
+    /// The search was cut short; `normal_forms` are those found so far.
+    Stopped { reason: StopReason, normal_forms: Vec<Graph<N, E>> },
+}
+
+impl<N, E> Exhaustive<N, E> {
+    /// The normal forms found, whether or not the search completed.
+    pub fn normal_forms(&self) -> &[Graph<N, E>] {
+        match self {
+            Exhaustive::NormalForms(graphs) | Exhaustive::Stopped { normal_forms: graphs, .. } => graphs,
+        }
+    }
+}
+
+/// Exhaustively apply a single rule along all rewrite branches and collect
+/// the graphs where no further match exists.
+///
+/// Graphs are identified up to isomorphism (by `canonical_hash`, confirmed
+/// with `is_isomorphic`), so each normal form is reported once and shared
+/// branches are explored once. Rules flagged by
+/// `Rule::is_trivially_nonterminating` are rejected up front if they match.
+/// A cycle does not abort the search, but the result reports it once the
+/// other branches are done; exceeding a limit stops at once.
+///
+/// # Errors
+/// Returns the `RuleError` if the rule is malformed.
+pub fn apply_exhaustive<N, E>(
+    host: &Graph<N, E>,
+    rule: &Rule<N, E>,
+    limits: ExhaustiveLimits,
+) -> Result<Exhaustive<N, E>, RuleError>
+where
+    N: Eq + Clone + Hash,
+    E: Eq + Clone + Hash,
+{
+    rule.validate()?;
+    if rule.is_trivially_nonterminating() && !dpo_find_matches(rule, host, true)?.is_empty() {
+        return Ok(Exhaustive::Stopped { reason: StopReason::NonTerminatingRule, normal_forms: Vec::new() });
+    }
+
+    let mut graphs = vec![host.clone()];
+    let mut on_path = vec![true];
+    let mut buckets: HashMap<u64, Vec<usize>> = HashMap::from([(canonical_hash(host), vec![0])]);
+    let mut steps = 0;
+    let mut normal_forms = Vec::new();
+    let mut cycle = false;
+
+    let successors = |g: &Graph<N, E>, steps: &mut usize| -> Result<Option<Vec<Graph<N, E>>>, RuleError> {
+        let mut next = Vec::new();
+        for m in dpo_find_matches(rule, g, true)? {
+            if *steps >= limits.max_steps {
+                return Ok(None);
+            }
+            match apply_once(rule, g, &m) {
+                Ok(d) => {
+                    *steps += 1;
+                    next.push(d.graph);
+                }
+                Err(RewriteError::InvalidRule(e)) => return Err(e),
+                Err(_) => {}
+            }
+        }
+        Ok(Some(next))
+    };
+    let stopped = |reason, normal_forms| Ok(Exhaustive::Stopped { reason, normal_forms });
+
+    // Depth-first, so a graph met again while still on the current path is a cycle
+    let Some(first) = successors(host, &mut steps)? else {
+        return stopped(StopReason::MaxSteps, normal_forms);
+    };
+    if first.is_empty() {
+        return Ok(Exhaustive::NormalForms(vec![host.clone()]));
+    }
+    let mut stack = vec![(0, first)];
+    while let Some((state, pending)) = stack.last_mut() {
+        let Some(g) = pending.pop() else {
+            on_path[*state] = false;
+            stack.pop();
+            continue;
+        };
+        let key = canonical_hash(&g);
+        if let Some(&s) = buckets.get(&key).and_then(|b| b.iter().find(|s| is_isomorphic(&graphs[**s], &g))) {
+            cycle |= on_path[s];
+            continue;
+        }
+        if graphs.len() >= limits.max_branches {
+            return stopped(StopReason::MaxBranches, normal_forms);
+        }
+        let Some(next) = successors(&g, &mut steps)? else {
+            return stopped(StopReason::MaxSteps, normal_forms);
+        };
+        buckets.entry(key).or_default().push(graphs.len());
+        if next.is_empty() {
+            on_path.push(false);
+            graphs.push(g.clone());
+            normal_forms.push(g);
+        } else {
+            on_path.push(true);
+            graphs.push(g);
+            stack.push((graphs.len() - 1, next));
+        }
+    }
+    if cycle {
+        stopped(StopReason::Cycle, normal_forms)
+    } else {
+        Ok(Exhaustive::NormalForms(normal_forms))
+    }
 }
 
 /// A builder for `Rule<N,E>` to avoid manually wiring up morphisms.
@@ -127,8 +508,10 @@ pub struct RuleBuilder<N, E> {
     lhs: Option<Graph<N, E>>,
     interface: Option<Graph<N, E>>,
     rhs: Option<Graph<N, E>>,
-    l2k: Option<Morphism>,
+    k2l: Option<Morphism>,
     k2r: Option<Morphism>,
+    merge_policy: MergePolicy<N>,
+    nacs: Vec<Nac<N, E>>,
 }
 
 impl<N: Clone, E: Clone> RuleBuilder<N, E> {
@@ -138,8 +521,10 @@ impl<N: Clone, E: Clone> RuleBuilder<N, E> {
             lhs: None,
             interface: None,
             rhs: None,
-            l2k: None,
+            k2l: None,
             k2r: None,
+            merge_policy: MergePolicy::Rhs,
+            nacs: Vec::new(),
         }
     }
 
@@ -158,8 +543,15 @@ impl<N: Clone, E: Clone> RuleBuilder<N, E> {
         self
     }
 
+    /// Set the L → K morphism; it is inverted into the rule's `k2l`.
     pub fn l2k(mut self, m: Morphism) -> Self {
-        self.l2k = Some(m);
+        self.k2l = Some(m.invert());
+        self
+    }
+
+    /// Set the K → L morphism directly, e.g. a non-injective one for cloning.
+    pub fn k2l(mut self, m: Morphism) -> Self {
+        self.k2l = Some(m);
         self
     }
 
@@ -168,20 +560,61 @@ impl<N: Clone, E: Clone> RuleBuilder<N, E> {
         self
     }
 
+    /// Set how node weights are combined when `k2r` merges nodes.
+    pub fn merge_policy(mut self, policy: MergePolicy<N>) -> Self {
+        self.merge_policy = policy;
+        self
+    }
+
+    /// Add a negative application condition: `graph` must not be embeddable
+    /// around the match, with `l2n` mapping the LHS into it.
+    pub fn nac(mut self, graph: Graph<N, E>, l2n: Morphism) -> Self {
+        self.nacs.push(Nac { graph, l2n });
+        self
+    }
+
+    /// Finalize into a `Rule`, checking that every component is present
+    /// and that the result is a well-formed rule (cloning is allowed).
+    ///
+    /// # Errors
+    /// Returns `RuleError::MissingComponent` for a missing part, or the
+    /// error reported by `Rule::validate_sqpo`.
+    pub fn try_build(self) -> Result<Rule<N, E>, RuleError> {
+        let rule = Rule {
+            lhs: self.lhs.ok_or(RuleError::MissingComponent("LHS graph"))?,
+            interface: self.interface.ok_or(RuleError::MissingComponent("interface graph"))?,
+            rhs: self.rhs.ok_or(RuleError::MissingComponent("RHS graph"))?,
+            k2l: self.k2l.ok_or(RuleError::MissingComponent("l2k or k2l morphism"))?,
+            k2r: self.k2r.ok_or(RuleError::MissingComponent("k2r morphism"))?,
+            merge_policy: self.merge_policy,
+            nacs: self.nacs,
+        };
+        rule.validate_sqpo()?;
+        Ok(rule)
+    }
+
     /// Finalize into a `Rule`. Panics if any component is missing.
     pub fn build(self) -> Rule<N, E> {
-        Rule::new(
-            self.lhs.expect("LHS graph required"),
-            self.interface.expect("Interface graph required"),
-            self.rhs.expect("RHS graph required"),
-            self.l2k.expect("l2k morphism required"),
-            self.k2r.expect("k2r morphism required"),
-        )
+        Rule {
+            lhs: self.lhs.expect("LHS graph required"),
+            interface: self.interface.expect("Interface graph required"),
+            rhs: self.rhs.expect("RHS graph required"),
+            k2l: self.k2l.expect("l2k or k2l morphism required"),
+            k2r: self.k2r.expect("k2r morphism required"),
+            merge_policy: self.merge_policy,
+            nacs: self.nacs,
+        }
     }
 }
 
 
 
+impl<N: Clone, E: Clone> Default for RuleBuilder<N, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Parse a Neo4j-style JSON string into a `petgraph::Graph<NodeData,String>`.
 /// 
 /// # Errors
@@ -205,7 +638,11 @@ where
     write_cypher_to_file(rule, path)
 }
 
-pub fn export_rule_to_cypher<N, E>(rule: &Rule<N, E>) -> String
+/// Export the given DPO `Rule` as a Cypher query string.
+///
+/// # Errors
+/// Returns the `RuleError` if the rule is malformed.
+pub fn export_rule_to_cypher<N, E>(rule: &Rule<N, E>) -> Result<String, RuleError>
 where
     N: Clone + Display,
     E: Clone + Display,
@@ -7,17 +7,43 @@ pub mod transformation;
 
 pub mod io;
 
+/// Static analysis of rules (critical pairs, confluence, dependencies,
+/// independence, termination), state-space exploration, temporal
+/// properties and derivation search.
+pub mod analysis;
+
 // Re-export key algorithmic functions
-pub use algorithms::{find_mappings, vf2_subgraph_isomorphism};
+pub use algorithms::{find_mappings, vf2_subgraph_isomorphism, is_isomorphic, find_isomorphism, find_isomorphism_by, canonical_hash};
 
 // Re-export core transformation types and functions
-pub use transformation::{Rule, Morphism, find_matches, apply_once, apply};
+pub use transformation::{
+    Rule, Morphism, find_matches, apply_once, apply_once_mut, apply, RuleSchema, Label,
+    Derivation, Comatch, InteractionScheme, MultiRule, Program,
+    RuleEngine, MatchOrder, Strategy, Grammar,
+    RuleError, RewriteError,
+};
+
+pub use analysis::{
+    critical_pairs, CriticalPair, ConflictKind,
+    check_local_confluence, JoinLimits, Joinability, PairVerdict,
+    dependency_graph, dependency_dot, application_order, DependencyKind,
+    are_parallel_independent, are_sequentially_independent, swap_steps,
+    explore, ExploreOptions, SearchOrder, StateSpace, Transition,
+    check_property, Formula, CheckResult,
+    Planner, Goal, Plan,
+    prove_termination, Termination, TerminationCertificate,
+};
 
 pub use io::neo4j::graph_from_neo4j_json;
 
+/// Seeded random and stochastic (Gillespie) rule application.
+pub mod simulate;
+pub use simulate::{random_walk, gillespie, Pick, StochasticRule, Trace, TraceEvent};
+
 pub mod api;
 pub use api::{
     MatchOptions, match_subgraphs, has_subgraph,
-    GraphRewrite, apply_rule, apply_rules, apply_exhaustive,
+    GraphRewrite, RewriteMode, apply_rule, apply_rule_with, apply_rules, apply_rules_in_order, apply_parallel, apply_exhaustive,
+    ExhaustiveLimits, Exhaustive, StopReason,
     RuleBuilder
 };
\ No newline at end of file
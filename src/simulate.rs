@@ -0,0 +1,189 @@
+// src/simulate.rs
+
+use petgraph::graph::Graph;
+use crate::algorithms::rng::SplitMix64;
+use crate::transformation::{Rule, Morphism, RuleError, RewriteError, Comatch};
+use crate::transformation::dpo::{find_matches, apply_once};
+use crate::transformation::util::check_gluing;
+
+/// How `random_walk` picks the next rewrite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pick {
+    /// Uniformly among all applicable (rule, match) pairs.
+    #[default]
+    UniformMatch,
+    /// Uniformly among the applicable rules, then uniformly among its matches.
+    UniformRule,
+}
+
+/// One rewrite step of a simulation.
+#[derive(Debug, Clone)]
+pub struct TraceEvent {
+    /// Simulated time after the step; the step number for `random_walk`.
+    pub time: f64,
+    /// Index of the applied rule.
+    pub rule: usize,
+    /// Where the rule's RHS landed in the graph after the step.
+    pub comatch: Comatch,
+}
+
+/// The time-stamped steps of a simulation and the graph it ended in.
+#[derive(Debug, Clone)]
+pub struct Trace<N, E> {
+    pub events: Vec<TraceEvent>,
+    pub graph: Graph<N, E>,
+}
+
+/// A rule firing with a given rate in a stochastic simulation.
+#[derive(Debug, Clone)]
+pub struct StochasticRule<N, E> {
+    pub rule: Rule<N, E>,
+    pub rate: f64,
+}
+
+impl<N, E> StochasticRule<N, E> {
+    /// Pair `rule` with its `rate`; a negative rate is treated as zero.
+    pub fn new(rule: Rule<N, E>, rate: f64) -> Self {
+        StochasticRule { rule, rate }
+    }
+}
+
+/// Apply randomly picked rules at randomly picked matches, up to
+/// `max_steps` times or until no rule applies.
+///
+/// Runs with the same rules, host and seed produce the same trace.
+///
+/// # Errors
+/// Returns the `RuleError` of the first malformed rule.
+pub fn random_walk<N, E>(
+    host: &Graph<N, E>,
+    rules: &[Rule<N, E>],
+    pick: Pick,
+    seed: u64,
+    max_steps: usize,
+) -> Result<Trace<N, E>, RuleError>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    let mut rng = SplitMix64::new(seed);
+    let mut graph = host.clone();
+    let mut events = Vec::new();
+    while events.len() < max_steps {
+        let candidates = applicable(rules, &graph)?;
+        let (rule, m) = match pick {
+            Pick::UniformMatch => {
+                let all: Vec<_> = candidates
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, ms)| ms.iter().map(move |m| (i, m)))
+                    .collect();
+                if all.is_empty() {
+                    break;
+                }
+                all[rng.below(all.len())]
+            }
+            Pick::UniformRule => {
+                let live: Vec<_> = (0..rules.len()).filter(|&i| !candidates[i].is_empty()).collect();
+                if live.is_empty() {
+                    break;
+                }
+                let i = live[rng.below(live.len())];
+                (i, &candidates[i][rng.below(candidates[i].len())])
+            }
+        };
+        let step = apply_once(&rules[rule], &graph, m).map_err(invalid_rule)?;
+        graph = step.graph;
+        events.push(TraceEvent { time: events.len() as f64 + 1.0, rule, comatch: step.comatch });
+    }
+    Ok(Trace { events, graph })
+}
+
+/// Run a Gillespie-style stochastic simulation.
+///
+/// The propensity of a rule is its rate times its number of applicable
+/// matches. Each step advances the simulated time by an exponentially
+/// distributed delay with the total propensity as parameter, then picks a
+/// rule proportionally to its propensity and one of its matches uniformly.
+/// The simulation stops once the next event would happen after `max_time`,
+/// after `max_steps` events, or when no rule applies.
+///
+/// # Errors
+/// Returns the `RuleError` of the first malformed rule.
+pub fn gillespie<N, E>(
+    host: &Graph<N, E>,
+    rules: &[StochasticRule<N, E>],
+    seed: u64,
+    max_time: f64,
+    max_steps: usize,
+) -> Result<Trace<N, E>, RuleError>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    let mut rng = SplitMix64::new(seed);
+    let mut graph = host.clone();
+    let mut events = Vec::new();
+    let mut time = 0.0;
+    while events.len() < max_steps {
+        let candidates = applicable(rules.iter().map(|r| &r.rule), &graph)?;
+        let propensities: Vec<f64> = rules
+            .iter()
+            .zip(&candidates)
+            .map(|(r, ms)| r.rate.max(0.0) * ms.len() as f64)
+            .collect();
+        let total: f64 = propensities.iter().sum();
+        if total <= 0.0 {
+            break;
+        }
+        time += -(1.0 - rng.next_f64()).ln() / total;
+        if time > max_time {
+            break;
+        }
+
+        // Pick a rule by propensity; fall back to the last live one on rounding
+        let mut target = rng.next_f64() * total;
+        let mut rule = propensities.iter().rposition(|&a| a > 0.0).unwrap();
+        for (i, &a) in propensities.iter().enumerate() {
+            if a > 0.0 && target < a {
+                rule = i;
+                break;
+            }
+            target -= a;
+        }
+        let m = &candidates[rule][rng.below(candidates[rule].len())];
+        let step = apply_once(&rules[rule].rule, &graph, m).map_err(invalid_rule)?;
+        graph = step.graph;
+        events.push(TraceEvent { time, rule, comatch: step.comatch });
+    }
+    Ok(Trace { events, graph })
+}
+
+/// The applicable matches of every rule, in `find_matches` order.
+fn applicable<'a, N, E>(
+    rules: impl IntoIterator<Item = &'a Rule<N, E>>,
+    host: &Graph<N, E>,
+) -> Result<Vec<Vec<Morphism>>, RuleError>
+where
+    N: Eq + Clone + 'a,
+    E: Eq + Clone + 'a,
+{
+    rules
+        .into_iter()
+        .map(|rule| {
+            rule.validate()?;
+            Ok(find_matches(rule, host, true)?
+                .into_iter()
+                .filter(|m| check_gluing(host, m, rule))
+                .collect())
+        })
+        .collect()
+}
+
+/// Unwrap the rule error of a step whose match was checked beforehand.
+fn invalid_rule(e: RewriteError) -> RuleError {
+    match e {
+        RewriteError::InvalidRule(e) => e,
+        other => unreachable!("applicable match failed to rewrite: {other}"),
+    }
+}
@@ -0,0 +1,225 @@
+use petgraph::graph::{Graph, NodeIndex, EdgeIndex};
+use std::collections::{HashMap, HashSet};
+use crate::transformation::{Rule, Morphism, RuleError, RewriteError};
+use crate::transformation::dpo::{find_matches, apply_once, Derivation};
+use crate::transformation::rule::validate_morphism;
+use crate::transformation::util::image;
+
+/// A multi-rule of an interaction scheme: a rule extending the kernel,
+/// with the embedding of the kernel's L, K and R into its own.
+#[derive(Debug, Clone)]
+pub struct MultiRule<N, E> {
+    pub rule: Rule<N, E>,
+    pub lhs: Morphism,
+    pub interface: Morphism,
+    pub rhs: Morphism,
+}
+
+/// A kernel rule plus multi-rules that extend it ("for all" rewriting).
+///
+/// The kernel is applied once; every multi-rule is applied at every
+/// extension of the kernel match, and all copies are amalgamated into a
+/// single DPO step. E.g. the kernel deletes a node `x` and a multi-rule
+/// deletes one outgoing `owns` edge of `x`, so the scheme deletes `x`
+/// with all its `owns` edges.
+#[derive(Debug, Clone)]
+pub struct InteractionScheme<N, E> {
+    pub kernel: Rule<N, E>,
+    pub multi_rules: Vec<MultiRule<N, E>>,
+}
+
+impl<N, E> InteractionScheme<N, E>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    /// Create a scheme with the given kernel and no multi-rules.
+    pub fn new(kernel: Rule<N, E>) -> Self {
+        InteractionScheme { kernel, multi_rules: Vec::new() }
+    }
+
+    /// Add a multi-rule, given with the embeddings of the kernel's LHS,
+    /// interface and RHS into its own.
+    pub fn with_multi_rule(mut self, rule: Rule<N, E>, lhs: Morphism, interface: Morphism, rhs: Morphism) -> Self {
+        self.multi_rules.push(MultiRule { rule, lhs, interface, rhs });
+        self
+    }
+
+    /// Validate the kernel, every multi-rule and their embeddings, which
+    /// must be total graph morphisms commuting with the spans.
+    ///
+    /// # Errors
+    /// Returns the `RuleError` describing the first problem found.
+    pub fn validate(&self) -> Result<(), RuleError> {
+        self.kernel.validate()?;
+        for multi in &self.multi_rules {
+            multi.rule.validate()?;
+            validate_morphism("embedding", &self.kernel.lhs, &multi.rule.lhs, &multi.lhs)?;
+            validate_morphism("embedding", &self.kernel.interface, &multi.rule.interface, &multi.interface)?;
+            validate_morphism("embedding", &self.kernel.rhs, &multi.rule.rhs, &multi.rhs)?;
+            for (kernel, embedding) in [
+                (&self.kernel.lhs, &multi.lhs),
+                (&self.kernel.interface, &multi.interface),
+                (&self.kernel.rhs, &multi.rhs),
+            ] {
+                if let Some(edge) = kernel.edge_indices().find(|e| !embedding.edge_map.contains_key(e)) {
+                    return Err(RuleError::NotTotalEdge { morphism: "embedding", edge });
+                }
+            }
+            for k in self.kernel.interface.node_indices() {
+                let via_k = multi.interface.node_map[&k];
+                if multi.rule.k2l.map_node(&via_k) != multi.lhs.map_node(&self.kernel.k2l.node_map[&k]) {
+                    return Err(RuleError::NotCommuting { morphism: "k2l", node: k });
+                }
+                if multi.rule.k2r.map_node(&via_k) != multi.rhs.map_node(&self.kernel.k2r.node_map[&k]) {
+                    return Err(RuleError::NotCommuting { morphism: "k2r", node: k });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Find all matches of the kernel in `host`.
+    ///
+    /// # Errors
+    /// Returns the `RuleError` if the scheme is malformed.
+    pub fn find_matches(&self, host: &Graph<N, E>) -> Result<Vec<Morphism>, RuleError> {
+        self.validate()?;
+        find_matches(&self.kernel, host, true)
+    }
+
+    /// Build the amalgamated rule for the kernel match `m`, together with
+    /// its match in `host`.
+    ///
+    /// The amalgamated rule glues one copy of every multi-rule per
+    /// extension of `m` onto the kernel. Copies may share host nodes and
+    /// edges outside the kernel match, so the resulting match need not be
+    /// injective, but an extension is skipped if it deletes something an
+    /// earlier copy matched or matches something an earlier copy deletes.
+    ///
+    /// # Errors
+    /// Returns `RewriteError::InvalidRule` if the scheme is malformed.
+    pub fn amalgamate(&self, host: &Graph<N, E>, m: &Morphism) -> Result<(Rule<N, E>, Morphism), RewriteError> {
+        self.validate()?;
+        let mut rule = self.kernel.clone();
+        let mut matched = m.clone();
+        let mut used = Footprint::default();
+        let mut deleted = Footprint::default();
+
+        for multi in &self.multi_rules {
+            let kernel_nodes: HashSet<NodeIndex> = multi.lhs.node_map.values().copied().collect();
+            let kernel_edges: HashSet<EdgeIndex> = multi.lhs.edge_map.values().copied().collect();
+            let (kept_nodes, kept_edges) = image(&multi.rule.k2l);
+            for ext in find_matches(&multi.rule, host, true)? {
+                // The extension must agree with the kernel match ...
+                let agrees = multi
+                    .lhs
+                    .node_map
+                    .iter()
+                    .all(|(k, l)| ext.map_node(l) == m.map_node(k));
+                if !agrees {
+                    continue;
+                }
+                // ... and must not conflict with an earlier copy over what
+                // either of them deletes outside the kernel
+                let mut uses = Footprint::default();
+                let mut deletes = Footprint::default();
+                for l in multi.rule.lhs.node_indices().filter(|l| !kernel_nodes.contains(l)) {
+                    uses.nodes.insert(ext.node_map[&l]);
+                    if !kept_nodes.contains(&l) {
+                        deletes.nodes.insert(ext.node_map[&l]);
+                    }
+                }
+                for e in multi.rule.lhs.edge_indices().filter(|e| !kernel_edges.contains(e)) {
+                    let (src, dst) = multi.rule.lhs.edge_endpoints(e).unwrap();
+                    let ends = (ext.node_map[&src], ext.node_map[&dst]);
+                    uses.edges.insert(ends);
+                    if !kept_edges.contains(&e) {
+                        deletes.edges.insert(ends);
+                    }
+                }
+                if !deletes.is_disjoint(&used) || !uses.is_disjoint(&deleted) {
+                    continue;
+                }
+                used.extend(uses);
+                deleted.extend(deletes);
+
+                let lhs = glue_copy(&mut rule.lhs, &multi.rule.lhs, &multi.lhs);
+                let interface = glue_copy(&mut rule.interface, &multi.rule.interface, &multi.interface);
+                let rhs = glue_copy(&mut rule.rhs, &multi.rule.rhs, &multi.rhs);
+                for (k, l) in &multi.rule.k2l.node_map {
+                    rule.k2l.insert_node(interface.node_map[k], lhs.node_map[l]);
+                }
+                for (k, l) in &multi.rule.k2l.edge_map {
+                    rule.k2l.insert_edge(interface.edge_map[k], lhs.edge_map[l]);
+                }
+                for (k, r) in &multi.rule.k2r.node_map {
+                    rule.k2r.insert_node(interface.node_map[k], rhs.node_map[r]);
+                }
+                for (k, r) in &multi.rule.k2r.edge_map {
+                    rule.k2r.insert_edge(interface.edge_map[k], rhs.edge_map[r]);
+                }
+                for (l, h) in &ext.node_map {
+                    matched.insert_node(lhs.node_map[l], *h);
+                }
+            }
+        }
+        Ok((rule, matched))
+    }
+
+    /// Apply the kernel at `m` together with every multi-rule extension,
+    /// as one amalgamated DPO step.
+    ///
+    /// # Errors
+    /// Returns `RewriteError::InvalidRule` if the scheme is malformed and
+    /// `RewriteError::DanglingCondition` if the amalgamated step fails gluing.
+    pub fn apply_once(&self, host: &Graph<N, E>, m: &Morphism) -> Result<Derivation<N, E>, RewriteError> {
+        let (rule, matched) = self.amalgamate(host, m)?;
+        apply_once(&rule, host, &matched)
+    }
+}
+
+/// Host nodes and edges, edges given by their endpoints as in rewriting.
+#[derive(Default)]
+struct Footprint {
+    nodes: HashSet<NodeIndex>,
+    edges: HashSet<(NodeIndex, NodeIndex)>,
+}
+
+impl Footprint {
+    fn is_disjoint(&self, other: &Footprint) -> bool {
+        self.nodes.is_disjoint(&other.nodes) && self.edges.is_disjoint(&other.edges)
+    }
+
+    fn extend(&mut self, other: Footprint) {
+        self.nodes.extend(other.nodes);
+        self.edges.extend(other.edges);
+    }
+}
+
+/// Glue a copy of `part` onto `target` along `embedding: kernel → part`,
+/// where `target` starts with the kernel graph at its original indices.
+/// Returns where each element of `part` ended up in `target`.
+fn glue_copy<N: Clone, E: Clone>(target: &mut Graph<N, E>, part: &Graph<N, E>, embedding: &Morphism) -> Morphism {
+    let kernel_nodes: HashMap<NodeIndex, NodeIndex> = embedding.node_map.iter().map(|(k, p)| (*p, *k)).collect();
+    let kernel_edges: HashMap<EdgeIndex, EdgeIndex> = embedding.edge_map.iter().map(|(k, p)| (*p, *k)).collect();
+    let mut placed = Morphism::new();
+    for n in part.node_indices() {
+        let at = match kernel_nodes.get(&n) {
+            Some(&k) => k,
+            None => target.add_node(part[n].clone()),
+        };
+        placed.insert_node(n, at);
+    }
+    for e in part.edge_indices() {
+        let at = match kernel_edges.get(&e) {
+            Some(&k) => k,
+            None => {
+                let (src, dst) = part.edge_endpoints(e).unwrap();
+                target.add_edge(placed.node_map[&src], placed.node_map[&dst], part[e].clone())
+            }
+        };
+        placed.insert_edge(e, at);
+    }
+    placed
+}
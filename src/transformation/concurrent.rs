@@ -0,0 +1,88 @@
+use petgraph::graph::{Graph, NodeIndex};
+use std::collections::HashMap;
+use crate::transformation::{Rule, Morphism, RewriteError};
+use crate::transformation::dpo::apply_once;
+use crate::transformation::overlap::{overlaps, is_overlap, glue};
+use crate::transformation::util::image;
+
+impl<N, E> Rule<N, E>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    /// Build the E-concurrent rule of `self` followed by `other`.
+    ///
+    /// `overlap` is a partial injective morphism R1 ⇀ L2 identifying the
+    /// elements the two rules share. The overlap graph E is the pushout of
+    /// R1 and L2 over it. Running `self` backwards on E yields the LHS of
+    /// the concurrent rule, running `other` forwards yields its RHS, and
+    /// the interface consists of the elements of E both steps preserve.
+    /// NACs of either rule are not carried over.
+    ///
+    /// # Errors
+    /// Returns `RewriteError::InvalidOverlap` if `overlap` is not injective
+    /// or label-preserving, `RewriteError::DanglingCondition` if either step
+    /// violates gluing on E, and `RewriteError::InvalidRule` if either rule
+    /// is malformed or `self` merges nodes.
+    pub fn compose(&self, other: &Rule<N, E>, overlap: Morphism) -> Result<Rule<N, E>, RewriteError> {
+        if !is_overlap(&self.rhs, &other.lhs, &overlap) {
+            return Err(RewriteError::InvalidOverlap);
+        }
+        let (e, r1e, l2e) = glue(&self.rhs, &other.lhs, &overlap);
+
+        // L ⇐ E ⇒ R
+        let undo = apply_once(&self.inverse(), &e, &r1e)?;
+        let redo = apply_once(other, &e, &l2e)?;
+
+        // K: the elements of E that survive both steps
+        let mut interface = Graph::new();
+        let mut k2l = Morphism::new();
+        let mut k2r = Morphism::new();
+        let mut e2k: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        for n in e.node_indices() {
+            if let (Some(&l), Some(&r)) = (undo.tracking.map_node(&n), redo.tracking.map_node(&n)) {
+                let k = interface.add_node(e[n].clone());
+                e2k.insert(n, k);
+                k2l.insert_node(k, l);
+                k2r.insert_node(k, r);
+            }
+        }
+        for edge in e.edge_indices() {
+            if let (Some(&l), Some(&r)) = (undo.tracking.map_edge(&edge), redo.tracking.map_edge(&edge)) {
+                let (src, dst) = e.edge_endpoints(edge).unwrap();
+                let k = interface.add_edge(e2k[&src], e2k[&dst], e[edge].clone());
+                k2l.insert_edge(k, l);
+                k2r.insert_edge(k, r);
+            }
+        }
+
+        Ok(Rule {
+            lhs: undo.graph,
+            interface,
+            rhs: redo.graph,
+            k2l,
+            k2r,
+            merge_policy: other.merge_policy.clone(),
+            nacs: Vec::new(),
+        })
+    }
+
+    /// Enumerate every overlap R1 ⇀ L2 under which `other` depends on `self`.
+    ///
+    /// An overlap qualifies if it is injective and label-preserving, uses at
+    /// least one element created by `self`, and `compose` accepts it. Edges
+    /// are identified whenever both endpoints are and a parallel L2 edge with
+    /// the same label is still free. Overlaps are returned in a
+    /// deterministic order.
+    pub fn dependency_overlaps(&self, other: &Rule<N, E>) -> Vec<Morphism> {
+        let (kept_nodes, kept_edges) = image(&self.k2r);
+        overlaps(&self.rhs, &other.lhs)
+            .into_iter()
+            .filter(|m| {
+                m.node_map.keys().any(|n| !kept_nodes.contains(n))
+                    || m.edge_map.keys().any(|e| !kept_edges.contains(e))
+            })
+            .filter(|m| self.compose(other, m.clone()).is_ok())
+            .collect()
+    }
+}
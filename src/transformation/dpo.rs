@@ -1,24 +1,27 @@
 use petgraph::graph::Graph;
 use crate::algorithms::vf2::find_mappings;
-use crate::transformation::{Rule, Morphism};
-use crate::transformation::util::{check_gluing, delete_part, add_part};
+use crate::transformation::{Rule, Morphism, RuleError, RewriteError};
+use crate::transformation::util::{check_gluing, check_match, delete_part, add_part, Tracker};
 
 /// Find all matches of the rule's LHS in the host graph.
-/// Validates the rule before matching.
+/// Validates the rule before matching; matches violating a NAC are skipped.
+///
+/// # Errors
+/// Returns the `RuleError` if the rule is malformed.
 pub fn find_matches<N, E>(
     rule: &Rule<N, E>,
     host: &Graph<N, E>,
     check_edge_labels: bool,
-) -> Vec<Morphism>
+) -> Result<Vec<Morphism>, RuleError>
 where
     N: Eq + Clone,
     E: Eq + Clone,
 {
-    // Ensure the DPO rule is well-formed
-    assert!(rule.validate(), "DPO rule validation failed");
+    // Ensure the rule is well-formed (cloning rules may be matched too)
+    rule.validate_sqpo()?;
     // Use VF2-based matcher to get node mappings
     let node_maps = find_mappings(&rule.lhs, host, check_edge_labels);
-    node_maps
+    Ok(node_maps
         .into_iter()
         .map(|node_map| {
             let mut m = Morphism::new();
@@ -27,71 +30,165 @@ where
             }
             m
         })
-        .collect()
+        .filter(|m| satisfies_nacs(rule, host, m, check_edge_labels))
+        .collect())
+}
+
+/// Check that match `m` satisfies every NAC of the rule, i.e. that no NAC
+/// graph embeds into `host` compatibly with `m`.
+pub fn satisfies_nacs<N, E>(
+    rule: &Rule<N, E>,
+    host: &Graph<N, E>,
+    m: &Morphism,
+    check_edge_labels: bool,
+) -> bool
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    rule.nacs.iter().all(|nac| {
+        !find_mappings(&nac.graph, host, check_edge_labels)
+            .iter()
+            .any(|q| {
+                m.node_map
+                    .iter()
+                    .all(|(l, h)| nac.l2n.map_node(l).and_then(|n| q.get(n)) == Some(h))
+            })
+    })
+}
+
+/// The comatch of a rewrite step: a morphism from the rule's RHS into the
+/// rewritten graph.
+pub type Comatch = Morphism;
+
+/// The outcome of a single rewrite step G ⇒ G'.
+#[derive(Debug, Clone)]
+pub struct Derivation<N, E> {
+    /// The rewritten graph G'.
+    pub graph: Graph<N, E>,
+    /// Where the RHS landed: R → G'.
+    pub comatch: Comatch,
+    /// Where the surviving host elements went: a partial morphism G → G'.
+    /// Deleted elements are unmapped; merged nodes share an image.
+    pub tracking: Morphism,
+}
+
+/// Apply a single DPO rewrite at the given match morphism, in place.
+/// Returns the comatch R → host.
+///
+/// The host is left untouched if the rule is invalid or gluing fails.
+///
+/// # Errors
+/// Returns `RewriteError::InvalidRule` if the rule is malformed,
+/// `RewriteError::IncompleteMatch` if `m` does not map every LHS node into
+/// the host and `RewriteError::DanglingCondition` if gluing fails.
+pub fn apply_once_mut<N, E>(
+    host: &mut Graph<N, E>,
+    rule: &Rule<N, E>,
+    m: &Morphism,
+) -> Result<Comatch, RewriteError>
+where
+    N: Clone,
+    E: Clone,
+{
+    let mut tracker = Tracker::new(host);
+    rewrite_tracked(host, rule, m, &mut tracker)
 }
 
 /// Apply a single DPO rewrite at the given match morphism.
-/// Returns the rewritten graph or `None` if gluing fails.
+/// Returns the rewritten copy of `host` together with the comatch and the
+/// tracking morphism; see `apply_once_mut`.
+///
+/// # Errors
+/// Returns `RewriteError::InvalidRule` if the rule is malformed,
+/// `RewriteError::IncompleteMatch` if `m` does not map every LHS node into
+/// the host and `RewriteError::DanglingCondition` if gluing fails.
 pub fn apply_once<N, E>(
     rule: &Rule<N, E>,
     host: &Graph<N, E>,
     m: &Morphism,
-) -> Option<Graph<N, E>>
+) -> Result<Derivation<N, E>, RewriteError>
+where
+    N: Clone,
+    E: Clone,
+{
+    let mut graph = host.clone();
+    let mut tracker = Tracker::new(host);
+    let comatch = rewrite_tracked(&mut graph, rule, m, &mut tracker)?;
+    Ok(Derivation { graph, comatch, tracking: tracker.morphism() })
+}
+
+/// DPO rewrite in place, recording removals in `tracker`.
+fn rewrite_tracked<N, E>(
+    host: &mut Graph<N, E>,
+    rule: &Rule<N, E>,
+    m: &Morphism,
+    tracker: &mut Tracker,
+) -> Result<Comatch, RewriteError>
 where
     N: Clone,
     E: Clone,
 {
     // Validate rule consistency
-    assert!(rule.validate(), "DPO rule validation failed");
+    rule.validate()?;
+    check_match(host, m, rule)?;
 
-    let mut result = host.clone();
     // Check gluing condition before rewriting
-    if !check_gluing(&result, m, rule) {
-        return None;
+    if !check_gluing(host, m, rule) {
+        return Err(RewriteError::DanglingCondition);
     }
     // Perform delete and add steps
-    delete_part(&mut result, m, rule);
-    add_part(&mut result, m, rule);
-    Some(result)
+    let m = delete_part(host, m, rule, tracker);
+    Ok(add_part(host, &m.compose(&rule.k2l), rule, tracker))
 }
 
 /// Apply the rule exhaustively until no more matches exist.
 /// Returns all endpoint graphs where the rule can no longer apply.
+///
+/// Every match is followed without a depth limit or memory of visited
+/// graphs, so this does not terminate on cyclic rules and may return
+/// isomorphic duplicates; see `analysis::explore` for a bounded search.
+///
+/// # Errors
+/// Returns the `RuleError` if the rule is malformed.
 pub fn apply<N, E>(
     rule: &Rule<N, E>,
     host: &Graph<N, E>,
     check_edge_labels: bool,
-) -> Vec<Graph<N, E>>
+) -> Result<Vec<Graph<N, E>>, RuleError>
 where
     N: Eq + Clone,
     E: Eq + Clone,
 {
     // Validate rule once up front
-    assert!(rule.validate(), "DPO rule validation failed");
+    rule.validate()?;
 
     fn recurse<N, E>(
         rule: &Rule<N, E>,
         current: Graph<N, E>,
         check_edge_labels: bool,
         results: &mut Vec<Graph<N, E>>,
-    )
+    ) -> Result<(), RuleError>
     where
         N: Eq + Clone,
         E: Eq + Clone,
     {
-        let matches = find_matches(rule, &current, check_edge_labels);
+        let matches = find_matches(rule, &current, check_edge_labels)?;
         if matches.is_empty() {
             results.push(current);
         } else {
             for m in matches {
-                if let Some(next) = apply_once(rule, &current, &m) {
-                    recurse(rule, next, check_edge_labels, results);
+                match apply_once(rule, &current, &m) {
+                    Ok(next) => recurse(rule, next.graph, check_edge_labels, results)?,
+                    Err(RewriteError::InvalidRule(e)) => return Err(e),
+                    Err(_) => {}
                 }
             }
         }
+        Ok(())
     }
 
     let mut finals = Vec::new();
-    recurse(rule, host.clone(), check_edge_labels, &mut finals);
-    finals
+    recurse(rule, host.clone(), check_edge_labels, &mut finals)?;
+    Ok(finals)
 }
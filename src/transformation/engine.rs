@@ -0,0 +1,195 @@
+use petgraph::graph::Graph;
+use crate::algorithms::rng::SplitMix64;
+use crate::transformation::{Rule, Morphism, RuleError, RewriteError, Comatch};
+use crate::transformation::dpo::{find_matches, apply_once_mut};
+use crate::transformation::util::check_gluing;
+
+/// A user key for ordering matches in a host graph.
+pub type MatchKey<N, E> = Box<dyn Fn(&Graph<N, E>, &Morphism) -> i64>;
+
+/// A user score for a rule at a match in a host graph.
+pub type MatchScore<N, E> = Box<dyn Fn(&Graph<N, E>, &Rule<N, E>, &Morphism) -> i64>;
+
+/// How the matches of a rule are ordered before a strategy picks one.
+pub enum MatchOrder<N, E> {
+    /// Lexicographically by the host nodes the LHS nodes map to, in LHS
+    /// index order.
+    HostIndex,
+    /// Ascending by a user key, ties broken by host index.
+    Key(MatchKey<N, E>),
+}
+
+/// How one rewrite is selected among the applicable candidates.
+pub enum Strategy<N, E> {
+    /// The first candidate: highest priority, then rule order, then match order.
+    First,
+    /// A uniformly random candidate, from a generator seeded once per engine.
+    Random { seed: u64 },
+    /// The candidate with the highest score; ties go to the earlier one.
+    Score(MatchScore<N, E>),
+}
+
+/// A rule engine with rule priorities and deterministic match selection.
+///
+/// Only rules of the highest priority that has an applicable match are
+/// considered in a step. Within that class candidates are ordered by rule
+/// insertion order and then by `MatchOrder`, so every run with the same
+/// rules, strategy and seed makes the same choices.
+pub struct RuleEngine<N, E> {
+    rules: Vec<(i32, Rule<N, E>)>,
+    order: MatchOrder<N, E>,
+    strategy: Strategy<N, E>,
+    rng: SplitMix64,
+}
+
+impl<N, E> RuleEngine<N, E>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    /// Create an engine without rules, ordering by host index and taking
+    /// the first candidate.
+    pub fn new() -> Self {
+        RuleEngine {
+            rules: Vec::new(),
+            order: MatchOrder::HostIndex,
+            strategy: Strategy::First,
+            rng: SplitMix64::new(0),
+        }
+    }
+
+    /// Add a rule; higher priorities are tried first.
+    pub fn rule(mut self, rule: Rule<N, E>, priority: i32) -> Self {
+        self.rules.push((priority, rule));
+        self
+    }
+
+    /// Set how matches are ordered.
+    pub fn match_order(mut self, order: MatchOrder<N, E>) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Set the selection strategy, reseeding the generator for `Random`.
+    pub fn strategy(mut self, strategy: Strategy<N, E>) -> Self {
+        if let Strategy::Random { seed } = strategy {
+            self.rng = SplitMix64::new(seed);
+        }
+        self.strategy = strategy;
+        self
+    }
+
+    /// The rules with their priorities, in insertion order.
+    pub fn rules(&self) -> impl Iterator<Item = (i32, &Rule<N, E>)> {
+        self.rules.iter().map(|(p, r)| (*p, r))
+    }
+
+    /// All applicable `(rule index, match)` pairs of the highest priority
+    /// class that has any, in candidate order.
+    ///
+    /// # Errors
+    /// Returns the `RuleError` of the first malformed rule.
+    pub fn candidates(&self, host: &Graph<N, E>) -> Result<Vec<(usize, Morphism)>, RuleError> {
+        let mut priorities: Vec<i32> = self.rules.iter().map(|(p, _)| *p).collect();
+        priorities.sort_unstable_by(|a, b| b.cmp(a));
+        priorities.dedup();
+        for priority in priorities {
+            let mut found = Vec::new();
+            for (i, (p, rule)) in self.rules.iter().enumerate() {
+                if *p != priority {
+                    continue;
+                }
+                rule.validate()?;
+                let mut matches: Vec<_> = find_matches(rule, host, true)?
+                    .into_iter()
+                    .filter(|m| check_gluing(host, m, rule))
+                    .map(|m| (self.key(host, &m), host_key(rule, &m), m))
+                    .collect();
+                matches.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+                found.extend(matches.into_iter().map(|(_, _, m)| (i, m)));
+            }
+            if !found.is_empty() {
+                return Ok(found);
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    /// Apply one selected rewrite to `host` in place.
+    /// Returns the index of the applied rule and the comatch, or `None` if
+    /// no rule is applicable.
+    ///
+    /// # Errors
+    /// Returns the `RuleError` of the first malformed rule.
+    pub fn step(&mut self, host: &mut Graph<N, E>) -> Result<Option<(usize, Comatch)>, RuleError> {
+        let candidates = self.candidates(host)?;
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+        let pick = match &self.strategy {
+            Strategy::First => 0,
+            Strategy::Random { .. } => self.rng.below(candidates.len()),
+            Strategy::Score(score) => {
+                let mut best = 0;
+                let mut best_score = i64::MIN;
+                for (j, (i, m)) in candidates.iter().enumerate() {
+                    let s = score(host, &self.rules[*i].1, m);
+                    if s > best_score {
+                        best = j;
+                        best_score = s;
+                    }
+                }
+                best
+            }
+        };
+        let (i, m) = &candidates[pick];
+        match apply_once_mut(host, &self.rules[*i].1, m) {
+            Ok(comatch) => Ok(Some((*i, comatch))),
+            Err(RewriteError::InvalidRule(e)) => Err(e),
+            // Candidates passed validation and gluing already
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Run up to `max_steps` steps, stopping early when no rule applies.
+    /// Returns the indices of the applied rules in order.
+    ///
+    /// # Errors
+    /// Returns the `RuleError` of the first malformed rule.
+    pub fn run(&mut self, host: &mut Graph<N, E>, max_steps: usize) -> Result<Vec<usize>, RuleError> {
+        let mut applied = Vec::new();
+        while applied.len() < max_steps {
+            match self.step(host)? {
+                Some((i, _)) => applied.push(i),
+                None => break,
+            }
+        }
+        Ok(applied)
+    }
+
+    /// The user key of `m`, or zero when ordering by host index only.
+    fn key(&self, host: &Graph<N, E>, m: &Morphism) -> i64 {
+        match &self.order {
+            MatchOrder::HostIndex => 0,
+            MatchOrder::Key(key) => key(host, m),
+        }
+    }
+}
+
+impl<N, E> Default for RuleEngine<N, E>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The host nodes of `m` in LHS index order.
+fn host_key<N, E>(rule: &Rule<N, E>, m: &Morphism) -> Vec<usize> {
+    rule.lhs
+        .node_indices()
+        .map(|l| m.map_node(&l).map_or(usize::MAX, |h| h.index()))
+        .collect()
+}
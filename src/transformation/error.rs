@@ -0,0 +1,46 @@
+use petgraph::graph::{NodeIndex, EdgeIndex};
+use thiserror::Error;
+
+/// Reasons a rule is malformed.
+///
+/// `morphism` names the offending morphism: `"k2l"` or `"k2r"` of the span,
+/// `"nac"` for the morphism of a negative application condition, or
+/// `"embedding"` for a kernel embedding of an interaction scheme.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RuleError {
+    #[error("missing rule component: {0}")]
+    MissingComponent(&'static str),
+    #[error("{morphism} maps node {node:?} from or to a node outside its graphs")]
+    DanglingNode { morphism: &'static str, node: NodeIndex },
+    #[error("{morphism} maps edge {edge:?} from or to an edge outside its graphs")]
+    DanglingEdge { morphism: &'static str, edge: EdgeIndex },
+    #[error("{morphism} does not map interface node {node:?}")]
+    NotTotal { morphism: &'static str, node: NodeIndex },
+    #[error("{morphism} does not map interface edge {edge:?}")]
+    NotTotalEdge { morphism: &'static str, edge: EdgeIndex },
+    #[error("{morphism} does not preserve the endpoints of edge {edge:?}")]
+    EdgeEndpointMismatch { morphism: &'static str, edge: EdgeIndex },
+    #[error("{morphism} maps several interface nodes onto node {node:?}")]
+    NotInjective { morphism: &'static str, node: NodeIndex },
+    #[error("{morphism} maps several interface edges onto edge {edge:?}")]
+    NotInjectiveEdge { morphism: &'static str, edge: EdgeIndex },
+    #[error("kernel embedding does not commute with {morphism} at interface node {node:?}")]
+    NotCommuting { morphism: &'static str, node: NodeIndex },
+    #[error("variable `{0}` is not bound by the left-hand side")]
+    UnboundVariable(String),
+}
+
+/// Reasons a single rewrite step cannot be performed.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RewriteError {
+    #[error("invalid rule: {0}")]
+    InvalidRule(#[from] RuleError),
+    #[error("no match of the left-hand side in the host graph")]
+    NoMatch,
+    #[error("gluing condition violated: deletion would leave dangling edges")]
+    DanglingCondition,
+    #[error("match does not map left-hand side node {node:?} into the host graph")]
+    IncompleteMatch { node: NodeIndex },
+    #[error("overlap is not an injective, label-preserving partial morphism")]
+    InvalidOverlap,
+}
@@ -0,0 +1,202 @@
+use petgraph::graph::Graph;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use crate::algorithms::iso::{canonical_hash, is_isomorphic};
+use crate::algorithms::rng::SplitMix64;
+use crate::transformation::{Rule, Morphism, RuleError, RewriteError};
+use crate::transformation::dpo::{find_matches, apply_once, satisfies_nacs, Derivation};
+use crate::transformation::util::check_gluing;
+
+/// A graph grammar: a start graph, terminal node labels and named rules.
+///
+/// Its language is the set of graphs derivable from the start graph whose
+/// node labels are all terminal. Size bounds count nodes plus edges, and
+/// every sentential form on the way must respect them.
+#[derive(Debug, Clone)]
+pub struct Grammar<N, E> {
+    pub start: Graph<N, E>,
+    pub terminals: Vec<N>,
+    pub rules: Vec<(String, Rule<N, E>)>,
+}
+
+impl<N, E> Grammar<N, E>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    /// Create a grammar with the given start graph, no terminals and no rules.
+    pub fn new(start: Graph<N, E>) -> Self {
+        Grammar { start, terminals: Vec::new(), rules: Vec::new() }
+    }
+
+    /// Declare a terminal node label.
+    pub fn terminal(mut self, label: N) -> Self {
+        self.terminals.push(label);
+        self
+    }
+
+    /// Add a named rule.
+    pub fn rule(mut self, name: impl Into<String>, rule: Rule<N, E>) -> Self {
+        self.rules.push((name.into(), rule));
+        self
+    }
+
+    /// The rule called `name`, if any.
+    pub fn rule_named(&self, name: &str) -> Option<&Rule<N, E>> {
+        self.rules.iter().find(|(n, _)| n == name).map(|(_, r)| r)
+    }
+
+    /// Whether every node label of `g` is terminal.
+    pub fn is_terminal(&self, g: &Graph<N, E>) -> bool {
+        g.node_weights().all(|l| self.terminals.contains(l))
+    }
+
+    /// Derive a random member of the language of size at most `max_size`.
+    ///
+    /// Starting from the start graph, applies a uniformly random rule and
+    /// match among those keeping the size within bounds, until the graph is
+    /// terminal. Returns `None` if the derivation gets stuck or takes more
+    /// than `max_steps` steps. The same seed gives the same result.
+    ///
+    /// # Errors
+    /// Returns the `RuleError` of the first malformed rule.
+    pub fn random_member(&self, seed: u64, max_size: usize, max_steps: usize) -> Result<Option<Graph<N, E>>, RuleError> {
+        for (_, rule) in &self.rules {
+            rule.validate()?;
+        }
+        let mut rng = SplitMix64::new(seed);
+        let mut graph = self.start.clone();
+        for _ in 0..=max_steps {
+            if self.is_terminal(&graph) {
+                return Ok((size(&graph) <= max_size).then_some(graph));
+            }
+            let mut candidates = Vec::new();
+            for (_, rule) in &self.rules {
+                if size(&graph) + size(&rule.rhs) > max_size + size(&rule.lhs) {
+                    continue;
+                }
+                for m in find_matches(rule, &graph, true)? {
+                    if check_gluing(&graph, &m, rule) {
+                        candidates.push((rule, m));
+                    }
+                }
+            }
+            if candidates.is_empty() {
+                return Ok(None);
+            }
+            let (rule, m) = &candidates[rng.below(candidates.len())];
+            graph = step(rule, &graph, m)?.expect("candidates satisfy gluing").graph;
+        }
+        Ok(None)
+    }
+
+    /// All members of the language of size at most `max_size`, up to
+    /// isomorphism, in breadth-first order of discovery.
+    ///
+    /// # Errors
+    /// Returns the `RuleError` of the first malformed rule.
+    pub fn enumerate(&self, max_size: usize) -> Result<Vec<Graph<N, E>>, RuleError>
+    where
+        N: Hash,
+        E: Hash,
+    {
+        let rules: Vec<Rule<N, E>> = self.rules.iter().map(|(_, r)| r.clone()).collect();
+        let forms = reachable(&self.start, &rules, max_size, |_, _, _| true)?;
+        Ok(forms.into_iter().filter(|g| self.is_terminal(g)).collect())
+    }
+
+    /// Check whether `g` is in the language by bounded parsing: rewriting
+    /// `g` backwards with the inverse rules, through sentential forms of
+    /// size at most `max_size`, until the start graph is reached.
+    ///
+    /// Each backward step is checked against the NACs of the forward rule.
+    /// Inverse rules relabel with `MergePolicy::Rhs`, so rules using another
+    /// merge policy may be parsed incorrectly.
+    ///
+    /// # Errors
+    /// Returns the `RuleError` of the first malformed rule.
+    pub fn contains(&self, g: &Graph<N, E>, max_size: usize) -> Result<bool, RuleError>
+    where
+        N: Hash,
+        E: Hash,
+    {
+        if !self.is_terminal(g) || size(g) > max_size {
+            return Ok(false);
+        }
+        let inverses: Vec<Rule<N, E>> = self.rules.iter().map(|(_, r)| r.inverse()).collect();
+        let start = canonical_hash(&self.start);
+        let mut found = false;
+        reachable(g, &inverses, max_size, |i, before, comatch| {
+            // The inverse step H ⇐ G is the forward step G ⇒ H at `comatch`
+            let allowed = satisfies_nacs(&self.rules[i].1, before, comatch, true);
+            found |= allowed && canonical_hash(before) == start && is_isomorphic(before, &self.start);
+            allowed && !found
+        })?;
+        Ok(found || is_isomorphic(g, &self.start))
+    }
+}
+
+/// The graphs reachable from `from` with `rules` through graphs of size
+/// at most `max_size`, up to isomorphism (bucketed by `canonical_hash`),
+/// in breadth-first order.
+/// `accept(rule, result, comatch)` may reject a step.
+fn reachable<N, E>(
+    from: &Graph<N, E>,
+    rules: &[Rule<N, E>],
+    max_size: usize,
+    mut accept: impl FnMut(usize, &Graph<N, E>, &Morphism) -> bool,
+) -> Result<Vec<Graph<N, E>>, RuleError>
+where
+    N: Eq + Clone + Hash,
+    E: Eq + Clone + Hash,
+{
+    for rule in rules {
+        rule.validate()?;
+    }
+    let mut forms = vec![from.clone()];
+    let mut buckets: HashMap<u64, Vec<usize>> = HashMap::from([(canonical_hash(from), vec![0])]);
+    let mut queue = VecDeque::from([0]);
+    while let Some(current) = queue.pop_front() {
+        for (i, rule) in rules.iter().enumerate() {
+            if size(&forms[current]) + size(&rule.rhs) > max_size + size(&rule.lhs) {
+                continue;
+            }
+            for m in find_matches(rule, &forms[current], true)? {
+                let Some(d) = step(rule, &forms[current], &m)? else {
+                    continue;
+                };
+                let next = d.graph;
+                if !accept(i, &next, &d.comatch) {
+                    continue;
+                }
+                let key = canonical_hash(&next);
+                let bucket = buckets.entry(key).or_default();
+                if bucket.iter().any(|s| is_isomorphic(&forms[*s], &next)) {
+                    continue;
+                }
+                bucket.push(forms.len());
+                queue.push_back(forms.len());
+                forms.push(next);
+            }
+        }
+    }
+    Ok(forms)
+}
+
+/// The number of nodes plus edges.
+fn size<N, E>(g: &Graph<N, E>) -> usize {
+    g.node_count() + g.edge_count()
+}
+
+/// One DPO step, or `None` if the gluing condition fails.
+fn step<N, E>(rule: &Rule<N, E>, g: &Graph<N, E>, m: &Morphism) -> Result<Option<Derivation<N, E>>, RuleError>
+where
+    N: Clone,
+    E: Clone,
+{
+    match apply_once(rule, g, m) {
+        Ok(d) => Ok(Some(d)),
+        Err(RewriteError::InvalidRule(e)) => Err(e),
+        Err(_) => Ok(None),
+    }
+}
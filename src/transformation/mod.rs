@@ -2,7 +2,24 @@ pub mod rule;
 pub mod morphism;
 pub mod util;
 pub mod dpo;
+pub mod spo;
+pub mod sqpo;
+pub mod schema;
+pub mod concurrent;
+pub mod overlap;
+pub mod amalgamation;
+pub mod program;
+pub mod engine;
+pub mod grammar;
+pub mod error;
 
-pub use rule::Rule;
+pub use rule::{Rule, MergePolicy, Nac};
 pub use morphism::Morphism;
-pub use dpo::{find_matches, apply_once, apply};
+pub use error::{RuleError, RewriteError};
+pub use dpo::{find_matches, satisfies_nacs, apply_once, apply_once_mut, apply, Comatch, Derivation};
+pub use sqpo::{apply_once_sqpo, apply_once_sqpo_mut};
+pub use schema::{RuleSchema, Label, Constraint, Binding, SchemaMatch};
+pub use amalgamation::{InteractionScheme, MultiRule};
+pub use program::Program;
+pub use engine::{RuleEngine, MatchOrder, Strategy, MatchKey, MatchScore};
+pub use grammar::Grammar;
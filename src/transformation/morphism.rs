@@ -73,3 +73,9 @@ impl Morphism {
         inv
     }
 }
+
+impl Default for Morphism {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,126 @@
+use petgraph::graph::{Graph, NodeIndex, EdgeIndex};
+use petgraph::visit::EdgeRef;
+use std::collections::HashSet;
+use crate::transformation::Morphism;
+
+/// Enumerate every partial injective, label-preserving morphism `a ⇀ b`.
+///
+/// Nodes of `a` are mapped to equally labelled nodes of `b` or left
+/// unmapped. An edge of `a` is identified with an edge of `b` whenever both
+/// endpoints are mapped and a parallel edge with the same label is still
+/// free. The empty morphism comes first; the order is deterministic.
+pub fn overlaps<N: Eq, E: Eq>(a: &Graph<N, E>, b: &Graph<N, E>) -> Vec<Morphism> {
+    let a_nodes: Vec<_> = a.node_indices().collect();
+    let mut out = Vec::new();
+    extend(a, b, &a_nodes, &mut Morphism::new(), &mut HashSet::new(), &mut out);
+    out
+}
+
+/// Map the remaining `a_nodes`, collecting every complete overlap.
+fn extend<N: Eq, E: Eq>(
+    a: &Graph<N, E>,
+    b: &Graph<N, E>,
+    a_nodes: &[NodeIndex],
+    current: &mut Morphism,
+    used: &mut HashSet<NodeIndex>,
+    out: &mut Vec<Morphism>,
+) {
+    let Some((&n, rest)) = a_nodes.split_first() else {
+        out.push(with_edges(a, b, current));
+        return;
+    };
+    extend(a, b, rest, current, used, out);
+    for target in b.node_indices() {
+        if !used.contains(&target) && a[n] == b[target] {
+            current.insert_node(n, target);
+            used.insert(target);
+            extend(a, b, rest, current, used, out);
+            used.remove(&target);
+            current.node_map.remove(&n);
+        }
+    }
+}
+
+/// Extend a node overlap with every edge of `a` that has a free parallel
+/// edge of the same label in `b`.
+fn with_edges<N, E: Eq>(a: &Graph<N, E>, b: &Graph<N, E>, nodes: &Morphism) -> Morphism {
+    let mut m = nodes.clone();
+    let mut used: HashSet<EdgeIndex> = HashSet::new();
+    for edge in a.edge_indices() {
+        let (src, dst) = a.edge_endpoints(edge).unwrap();
+        let (Some(&b_src), Some(&b_dst)) = (m.map_node(&src), m.map_node(&dst)) else {
+            continue;
+        };
+        if let Some(b_edge) = b
+            .edges_connecting(b_src, b_dst)
+            .find(|c| !used.contains(&c.id()) && *c.weight() == a[edge])
+            .map(|c| c.id())
+        {
+            used.insert(b_edge);
+            m.insert_edge(edge, b_edge);
+        }
+    }
+    m
+}
+
+/// Whether `overlap` is an injective, label-preserving partial morphism
+/// `a ⇀ b` whose edge images run between the images of their endpoints.
+pub fn is_overlap<N: Eq, E: Eq>(a: &Graph<N, E>, b: &Graph<N, E>, overlap: &Morphism) -> bool {
+    let mut seen = HashSet::new();
+    for (&n, &target) in &overlap.node_map {
+        match (a.node_weight(n), b.node_weight(target)) {
+            (Some(x), Some(y)) if x == y && seen.insert(target) => {}
+            _ => return false,
+        }
+    }
+    let mut seen = HashSet::new();
+    overlap.edge_map.iter().all(|(&edge, &target)| {
+        let (Some((src, dst)), Some((t_src, t_dst))) = (a.edge_endpoints(edge), b.edge_endpoints(target)) else {
+            return false;
+        };
+        overlap.map_node(&src) == Some(&t_src)
+            && overlap.map_node(&dst) == Some(&t_dst)
+            && a[edge] == b[target]
+            && seen.insert(target)
+    })
+}
+
+/// Glue `a` and `b` along `overlap: a ⇀ b`, i.e. build their pushout.
+///
+/// The result is a copy of `b` (same indices) extended by the elements of
+/// `a` outside the overlap. Returns the glued graph with the injections of
+/// `a` and `b` into it.
+pub fn glue<N: Clone, E: Clone>(
+    a: &Graph<N, E>,
+    b: &Graph<N, E>,
+    overlap: &Morphism,
+) -> (Graph<N, E>, Morphism, Morphism) {
+    let mut glued = b.clone();
+    let mut b2g = Morphism::new();
+    for n in b.node_indices() {
+        b2g.insert_node(n, n);
+    }
+    for edge in b.edge_indices() {
+        b2g.insert_edge(edge, edge);
+    }
+    let mut a2g = Morphism::new();
+    for n in a.node_indices() {
+        let target = match overlap.map_node(&n) {
+            Some(&t) => t,
+            None => glued.add_node(a[n].clone()),
+        };
+        a2g.insert_node(n, target);
+    }
+    for edge in a.edge_indices() {
+        let target = match overlap.map_edge(&edge) {
+            Some(&t) => t,
+            None => {
+                let (src, dst) = a.edge_endpoints(edge).unwrap();
+                let (src, dst) = (a2g.node_map[&src], a2g.node_map[&dst]);
+                glued.add_edge(src, dst, a[edge].clone())
+            }
+        };
+        a2g.insert_edge(edge, target);
+    }
+    (glued, a2g, b2g)
+}
@@ -0,0 +1,141 @@
+use petgraph::graph::Graph;
+use crate::transformation::{Rule, RuleError, RewriteError};
+use crate::transformation::dpo::{find_matches, apply_once};
+
+/// A GP2-style graph program controlling how rules are applied.
+///
+/// Programs either succeed with a result graph or fail. Nondeterministic
+/// choices (rule sets, matches and `Or`) are resolved by backtracking: if
+/// the rest of the program fails, the next alternative is tried, in rule
+/// order, then match order as returned by `find_matches`.
+#[derive(Debug, Clone)]
+pub enum Program<N, E> {
+    /// Succeed without changing the graph.
+    Skip,
+    /// Fail.
+    Fail,
+    /// Apply one of the rules at one of its matches; fail if none applies.
+    Rules(Vec<Rule<N, E>>),
+    /// Run the programs one after another.
+    Seq(Vec<Program<N, E>>),
+    /// `P!`: run P as long as it succeeds, then succeed with the last graph.
+    /// Each successful iteration is committed to; loops over a program that
+    /// always succeeds do not terminate.
+    AsLongAsPossible(Box<Program<N, E>>),
+    /// `if C then P else Q`: run P if C succeeds, Q otherwise, both on the
+    /// original graph; the result of C is discarded.
+    If(Box<Program<N, E>>, Box<Program<N, E>>, Box<Program<N, E>>),
+    /// `try C then P else Q`: like `If`, but P continues from the result of C.
+    Try(Box<Program<N, E>>, Box<Program<N, E>>, Box<Program<N, E>>),
+    /// `P or Q`: run P, or Q if the rest of the program fails after P.
+    Or(Box<Program<N, E>>, Box<Program<N, E>>),
+}
+
+/// Continuation receiving the graph produced so far.
+type Cont<'a, N, E> = dyn FnMut(Graph<N, E>) -> Result<Option<Graph<N, E>>, RuleError> + 'a;
+
+impl<N, E> Program<N, E>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    /// A program applying a single rule once.
+    pub fn rule(rule: Rule<N, E>) -> Self {
+        Program::Rules(vec![rule])
+    }
+
+    /// `self!`
+    pub fn alap(self) -> Self {
+        Program::AsLongAsPossible(Box::new(self))
+    }
+
+    /// `self or other`
+    pub fn or(self, other: Program<N, E>) -> Self {
+        Program::Or(Box::new(self), Box::new(other))
+    }
+
+    /// `if cond then then_branch else else_branch`
+    pub fn if_then_else(cond: Program<N, E>, then_branch: Program<N, E>, else_branch: Program<N, E>) -> Self {
+        Program::If(Box::new(cond), Box::new(then_branch), Box::new(else_branch))
+    }
+
+    /// `try cond then then_branch else else_branch`
+    pub fn try_then_else(cond: Program<N, E>, then_branch: Program<N, E>, else_branch: Program<N, E>) -> Self {
+        Program::Try(Box::new(cond), Box::new(then_branch), Box::new(else_branch))
+    }
+
+    /// Run the program on a copy of `host`.
+    /// Returns the result graph, or `None` if the program fails.
+    ///
+    /// # Errors
+    /// Returns the `RuleError` of the first malformed rule encountered.
+    pub fn run(&self, host: &Graph<N, E>) -> Result<Option<Graph<N, E>>, RuleError> {
+        self.exec(host.clone(), &mut |g| Ok(Some(g)))
+    }
+
+    /// Run the program on `graph` and pass every result to `k` until it
+    /// accepts one, backtracking into the remaining choices otherwise.
+    fn exec(&self, graph: Graph<N, E>, k: &mut Cont<'_, N, E>) -> Result<Option<Graph<N, E>>, RuleError> {
+        match self {
+            Program::Skip => k(graph),
+            Program::Fail => Ok(None),
+            Program::Rules(rules) => {
+                for rule in rules {
+                    for m in find_matches(rule, &graph, true)? {
+                        match apply_once(rule, &graph, &m) {
+                            Ok(d) => {
+                                if let Some(result) = k(d.graph)? {
+                                    return Ok(Some(result));
+                                }
+                            }
+                            Err(RewriteError::InvalidRule(e)) => return Err(e),
+                            Err(_) => {}
+                        }
+                    }
+                }
+                Ok(None)
+            }
+            Program::Seq(programs) => exec_seq(programs, graph, k),
+            Program::AsLongAsPossible(body) => {
+                let mut current = graph;
+                while let Some(next) = body.exec(current.clone(), &mut |g| Ok(Some(g)))? {
+                    current = next;
+                }
+                k(current)
+            }
+            Program::If(cond, then_branch, else_branch) => {
+                if cond.exec(graph.clone(), &mut |g| Ok(Some(g)))?.is_some() {
+                    then_branch.exec(graph, k)
+                } else {
+                    else_branch.exec(graph, k)
+                }
+            }
+            Program::Try(cond, then_branch, else_branch) => {
+                match cond.exec(graph.clone(), &mut |g| Ok(Some(g)))? {
+                    Some(result) => then_branch.exec(result, k),
+                    None => else_branch.exec(graph, k),
+                }
+            }
+            Program::Or(left, right) => match left.exec(graph.clone(), k)? {
+                Some(result) => Ok(Some(result)),
+                None => right.exec(graph, k),
+            },
+        }
+    }
+}
+
+/// Run `programs` in sequence, then hand the result to `k`.
+fn exec_seq<N, E>(
+    programs: &[Program<N, E>],
+    graph: Graph<N, E>,
+    k: &mut Cont<'_, N, E>,
+) -> Result<Option<Graph<N, E>>, RuleError>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    match programs.split_first() {
+        None => k(graph),
+        Some((first, rest)) => first.exec(graph, &mut |g| exec_seq(rest, g, k)),
+    }
+}
@@ -1,21 +1,62 @@
-use petgraph::graph::Graph;
+use petgraph::graph::{Graph, NodeIndex, EdgeIndex};
 use crate::transformation::morphism::Morphism;
+use crate::transformation::error::RuleError;
 use std::fmt::Display;
+use std::collections::HashSet;
 // use std::collections::HashMap;
 
 /// A DPO rewrite rule defined by a span of morphisms L <- K -> R.
 ///
+/// The span is stored in its conventional orientation, so rewriting reads
+/// both morphisms forwards and never has to invert them.
+///
 /// - `lhs`: left-hand side graph L.
 /// - `interface`: interface graph K (common part).
 /// - `rhs`: right-hand side graph R.
-/// - `l2k`: morphism from L to K.
+/// - `k2l`: morphism from K to L.
 /// - `k2r`: morphism from K to R.
+/// - `merge_policy`: how to weight host nodes that are preserved or fused.
+/// - `nacs`: negative application conditions restricting where L may match.
+///
+/// For DPO and SPO rewriting `k2l` must be injective. A `k2l` that maps
+/// several interface nodes onto the same LHS node describes cloning and is
+/// only supported by SqPO rewriting (see `sqpo::apply_once_sqpo`).
+/// A `k2r` that maps several interface nodes onto the same RHS node merges
+/// their host images into one node under every semantics.
+#[derive(Debug, Clone)]
 pub struct Rule<N, E> {
     pub lhs: Graph<N, E>,
     pub interface: Graph<N, E>,
     pub rhs: Graph<N, E>,
-    pub l2k: Morphism,
+    pub k2l: Morphism,
     pub k2r: Morphism,
+    pub merge_policy: MergePolicy<N>,
+    pub nacs: Vec<Nac<N, E>>,
+}
+
+/// A negative application condition: an extension `l2n: L → graph` of the
+/// LHS. A match satisfies it if it cannot be extended to an injective
+/// embedding of `graph` into the host.
+#[derive(Debug, Clone)]
+pub struct Nac<N, E> {
+    pub graph: Graph<N, E>,
+    pub l2n: Morphism,
+}
+
+/// How to weight a host node that a rule preserves, possibly merging
+/// several host nodes into it, and the host edges it preserves.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum MergePolicy<N> {
+    /// Use the weight of the RHS node the interface nodes are mapped to, so
+    /// preserved nodes and edges are relabelled to their RHS label.
+    #[default]
+    Rhs,
+    /// Keep the weight of the surviving host node (the image of the
+    /// lowest-indexed interface node) and of every preserved edge.
+    Survivor,
+    /// Fold the weights of all merged host nodes, in interface index order.
+    /// Other preserved nodes and edges keep their host weight.
+    Combine(fn(&N, &N) -> N),
 }
 
 impl<N, E> Rule<N, E>
@@ -24,6 +65,8 @@ where
     E: Clone,
 {
     /// Construct a new DPO rule from its components.
+    ///
+    /// `l2k` maps L to K and is inverted into the stored `k2l`.
     pub fn new(
         lhs: Graph<N, E>,
         interface: Graph<N, E>,
@@ -31,66 +74,340 @@ where
         l2k: Morphism,
         k2r: Morphism,
     ) -> Self {
-        Rule { lhs, interface, rhs, l2k, k2r }
+        Rule {
+            lhs,
+            interface,
+            rhs,
+            k2l: l2k.invert(),
+            k2r,
+            merge_policy: MergePolicy::Rhs,
+            nacs: Vec::new(),
+        }
     }
 
-    /// Validate that the morphisms are well-defined between the graphs.
-    /// Checks that:
-    /// 1. Nodes in `lhs` map to nodes in `interface`.
-    /// 2. Edges in `lhs` map to edges in `interface` with matching endpoints.
-    /// 3. Nodes in `interface` map to nodes in `rhs`.
-    /// 4. Edges in `interface` map to edges in `rhs` with matching endpoints.
-    pub fn validate(&self) -> bool {
-        // 1. Validate l2k node mappings: source in lhs, target in interface
-        for (&l_node, &k_node) in &self.l2k.node_map {
-            if !self.lhs.node_indices().any(|n| n == l_node) {
-                return false;
+    /// Construct a DPO rule from a span L <- K -> R given as the standard
+    /// injections `k2l` and `k2r`.
+    ///
+    /// # Errors
+    /// Returns the `RuleError` describing why `k2l` or `k2r` is not a total,
+    /// injective graph morphism.
+    pub fn from_span(
+        lhs: Graph<N, E>,
+        interface: Graph<N, E>,
+        rhs: Graph<N, E>,
+        k2l: Morphism,
+        k2r: Morphism,
+    ) -> Result<Self, RuleError> {
+        let rule = Rule {
+            lhs,
+            interface,
+            rhs,
+            k2l,
+            k2r,
+            merge_policy: MergePolicy::Rhs,
+            nacs: Vec::new(),
+        };
+        rule.validate()?;
+        for (name, m) in [("k2l", &rule.k2l), ("k2r", &rule.k2r)] {
+            if let Some(edge) = rule.interface.edge_indices().find(|e| !m.edge_map.contains_key(e)) {
+                return Err(RuleError::NotTotalEdge { morphism: name, edge });
             }
-            if !self.interface.node_indices().any(|n| n == k_node) {
-                return false;
+            if let Some(node) = duplicate_image(m) {
+                return Err(RuleError::NotInjective { morphism: name, node });
             }
-        }
-        // 2. Validate l2k edge mappings: domain edges map and endpoints preserved
-        for (&l_edge, &k_edge) in &self.l2k.edge_map {
-            if let (Some((l_src, l_dst)), Some((k_src, k_dst))) = (
-                self.lhs.edge_endpoints(l_edge),
-                self.interface.edge_endpoints(k_edge)
-            ) {
-                let mapped_src = self.l2k.node_map.get(&l_src).unwrap();
-                let mapped_dst = self.l2k.node_map.get(&l_dst).unwrap();
-                if *mapped_src != k_src || *mapped_dst != k_dst {
-                    return false;
-                }
-            } else {
-                return false;
+            let mut edges: Vec<_> = m.edge_map.values().collect();
+            edges.sort_unstable();
+            if let Some(w) = edges.windows(2).find(|w| w[0] == w[1]) {
+                return Err(RuleError::NotInjectiveEdge { morphism: name, edge: *w[0] });
             }
         }
-        // 3. Validate k2r node mappings: source in interface, target in rhs
-        for (&k_node, &r_node) in &self.k2r.node_map {
-            if !self.interface.node_indices().any(|n| n == k_node) {
-                return false;
+        Ok(rule)
+    }
+
+    /// Set how node weights are combined when `k2r` merges nodes.
+    pub fn with_merge_policy(mut self, policy: MergePolicy<N>) -> Self {
+        self.merge_policy = policy;
+        self
+    }
+
+    /// Add a negative application condition given by `graph` and the
+    /// morphism `l2n` from the LHS into it.
+    pub fn with_nac(mut self, graph: Graph<N, E>, l2n: Morphism) -> Self {
+        self.nacs.push(Nac { graph, l2n });
+        self
+    }
+
+    /// Validate that the rule is a well-formed DPO rule.
+    /// Checks that:
+    /// 1. Every node in `interface` maps to a node in `lhs`, injectively.
+    /// 2. Edges in `interface` map to edges in `lhs` with matching endpoints.
+    /// 3. Every node in `interface` maps to a node in `rhs`.
+    /// 4. Edges in `interface` map to edges in `rhs` with matching endpoints.
+    /// 5. Every NAC morphism is a graph morphism from `lhs` into its NAC.
+    pub fn validate(&self) -> Result<(), RuleError> {
+        self.validate_sqpo()?;
+        match duplicate_image(&self.k2l) {
+            Some(node) => Err(RuleError::NotInjective { morphism: "k2l", node }),
+            None => Ok(()),
+        }
+    }
+
+    /// Validate that the rule is a well-formed SqPO rule.
+    /// Same as `validate`, except that `k2l` may identify interface nodes,
+    /// which clones the matched LHS node once per preimage.
+    pub fn validate_sqpo(&self) -> Result<(), RuleError> {
+        validate_morphism("k2l", &self.interface, &self.lhs, &self.k2l)?;
+        validate_morphism("k2r", &self.interface, &self.rhs, &self.k2r)?;
+        for nac in &self.nacs {
+            validate_morphism("nac", &self.lhs, &nac.graph, &nac.l2n)?;
+        }
+        Ok(())
+    }
+
+    /// The inverse rule R <- K -> L, which undoes this rule.
+    ///
+    /// Applying the inverse at the comatch of a derivation restores the
+    /// original graph up to isomorphism. The inverse of a merging rule
+    /// clones, so it can only be applied with SqPO rewriting. The merge
+    /// policy is reset to `MergePolicy::Rhs` and NACs are dropped, since
+    /// they constrain L rather than R.
+    pub fn inverse(&self) -> Rule<N, E> {
+        Rule {
+            lhs: self.rhs.clone(),
+            interface: self.interface.clone(),
+            rhs: self.lhs.clone(),
+            k2l: self.k2r.clone(),
+            k2r: self.k2l.clone(),
+            merge_policy: MergePolicy::Rhs,
+            nacs: Vec::new(),
+        }
+    }
+
+    /// The parallel rule `self + other`, the disjoint union of both spans.
+    ///
+    /// The elements of `other` follow those of `self` in every graph, so an
+    /// index `i` of `other.lhs` becomes `i + self.lhs.node_count()` (and
+    /// likewise for edges, K and R). Each NAC is extended by the other LHS.
+    /// The merge policy of `self` is kept.
+    pub fn parallel(&self, other: &Rule<N, E>) -> Rule<N, E> {
+        let lhs = disjoint_union(&self.lhs, &other.lhs);
+        let nacs = self
+            .nacs
+            .iter()
+            .map(|nac| Nac {
+                graph: disjoint_union(&nac.graph, &other.lhs),
+                l2n: union_morphism(&self.lhs, &nac.graph, &nac.l2n, &identity(&other.lhs)),
+            })
+            .chain(other.nacs.iter().map(|nac| Nac {
+                graph: disjoint_union(&self.lhs, &nac.graph),
+                l2n: union_morphism(&self.lhs, &self.lhs, &identity(&self.lhs), &nac.l2n),
+            }))
+            .collect();
+        Rule {
+            lhs,
+            interface: disjoint_union(&self.interface, &other.interface),
+            rhs: disjoint_union(&self.rhs, &other.rhs),
+            k2l: union_morphism(&self.interface, &self.lhs, &self.k2l, &other.k2l),
+            k2r: union_morphism(&self.interface, &self.rhs, &self.k2r, &other.k2r),
+            merge_policy: self.merge_policy.clone(),
+            nacs,
+        }
+    }
+
+    /// The parallel rule `self + … + self` with `k` copies, built in one
+    /// pass. It equals folding `parallel` over `k` copies of `self`: copy
+    /// `j` of every graph starts at `j` times its node and edge counts, and
+    /// the NACs of copy `j` are extended by the other copies of the LHS.
+    ///
+    /// # Panics
+    /// Panics if `k` is zero.
+    pub fn parallel_copies(&self, k: usize) -> Rule<N, E> {
+        assert!(k > 0, "a parallel rule needs at least one copy");
+        let size = |g: &Graph<N, E>| (g.node_count(), g.edge_count());
+        let at = |(n, e): (usize, usize), j: usize| (n * j, e * j);
+        let span = |m: &Morphism, src: &Graph<N, E>, dst: &Graph<N, E>| {
+            let mut union = Morphism::new();
+            for j in 0..k {
+                shift_into(&mut union, m, at(size(src), j), at(size(dst), j));
             }
-            if !self.rhs.node_indices().any(|n| n == r_node) {
-                return false;
+            union
+        };
+        let mut nacs = Vec::new();
+        for j in 0..k {
+            for nac in &self.nacs {
+                // Copies before `j`, then the NAC graph, then copies after `j`
+                let mut graph = Graph::new();
+                let mut l2n = Morphism::new();
+                for i in 0..k {
+                    let offset = size(&graph);
+                    if i == j {
+                        append(&mut graph, &nac.graph);
+                        shift_into(&mut l2n, &nac.l2n, at(size(&self.lhs), i), offset);
+                    } else {
+                        append(&mut graph, &self.lhs);
+                        shift_into(&mut l2n, &identity(&self.lhs), at(size(&self.lhs), i), offset);
+                    }
+                }
+                nacs.push(Nac { graph, l2n });
             }
         }
-        // 4. Validate k2r edge mappings: domain edges map and endpoints preserved
-        for (&k_edge, &r_edge) in &self.k2r.edge_map {
-            if let (Some((k_src, k_dst)), Some((r_src, r_dst))) = (
-                self.interface.edge_endpoints(k_edge),
-                self.rhs.edge_endpoints(r_edge)
-            ) {
-                let mapped_src = self.k2r.node_map.get(&k_src).unwrap();
-                let mapped_dst = self.k2r.node_map.get(&k_dst).unwrap();
-                if *mapped_src != r_src || *mapped_dst != r_dst {
-                    return false;
-                }
-            } else {
+        Rule {
+            lhs: copies(&self.lhs, k),
+            interface: copies(&self.interface, k),
+            rhs: copies(&self.rhs, k),
+            k2l: span(&self.k2l, &self.interface, &self.lhs),
+            k2r: span(&self.k2r, &self.interface, &self.rhs),
+            merge_policy: self.merge_policy.clone(),
+            nacs,
+        }
+    }
+
+    /// Whether `k2l` maps two interface nodes onto the same LHS node.
+    pub fn clones_nodes(&self) -> bool {
+        duplicate_image(&self.k2l).is_some()
+    }
+
+    /// Whether the rule trivially never terminates: it has no NAC, deletes
+    /// nothing and its LHS embeds into its RHS along the span with the same
+    /// labels (LHS ⊆ RHS), so every result of applying it has a match again.
+    /// Under `MergePolicy::Survivor` preserved elements keep their host
+    /// labels, so only the shape of the span counts.
+    pub fn is_trivially_nonterminating(&self) -> bool
+    where
+        N: Eq,
+        E: Eq,
+    {
+        if !self.nacs.is_empty() || matches!(self.merge_policy, MergePolicy::Combine(_)) {
+            return false;
+        }
+        let relabels = matches!(self.merge_policy, MergePolicy::Rhs);
+        let mut images = HashSet::new();
+        let nodes_kept = self.lhs.node_indices().all(|l| {
+            let Some((k, _)) = self.k2l.node_map.iter().filter(|(_, v)| **v == l).min_by_key(|(k, _)| **k) else {
                 return false;
+            };
+            match self.k2r.map_node(k) {
+                Some(r) => (!relabels || self.rhs[*r] == self.lhs[l]) && images.insert(*r),
+                None => false,
             }
+        });
+        nodes_kept
+            && self.lhs.edge_indices().all(|l| {
+                self.k2l
+                    .edge_map
+                    .iter()
+                    .filter(|(_, v)| **v == l)
+                    .any(|(k, _)| self.k2r.map_edge(k).is_some_and(|r| !relabels || self.rhs[*r] == self.lhs[l]))
+            })
+    }
+}
+
+/// Append a copy of `b` to a copy of `a`, shifting the indices of `b` by
+/// the node and edge counts of `a`.
+fn disjoint_union<N: Clone, E: Clone>(a: &Graph<N, E>, b: &Graph<N, E>) -> Graph<N, E> {
+    let mut union = a.clone();
+    append(&mut union, b);
+    union
+}
+
+/// The disjoint union of `k` copies of `g`.
+fn copies<N: Clone, E: Clone>(g: &Graph<N, E>, k: usize) -> Graph<N, E> {
+    let mut union = Graph::with_capacity(g.node_count() * k, g.edge_count() * k);
+    for _ in 0..k {
+        append(&mut union, g);
+    }
+    union
+}
+
+/// Append a copy of `b` to `union`, shifting its indices by the node and
+/// edge counts of `union`.
+fn append<N: Clone, E: Clone>(union: &mut Graph<N, E>, b: &Graph<N, E>) {
+    let offset = union.node_count();
+    for n in b.node_indices() {
+        union.add_node(b[n].clone());
+    }
+    for e in b.edge_indices() {
+        let (src, dst) = b.edge_endpoints(e).unwrap();
+        union.add_edge(
+            NodeIndex::new(src.index() + offset),
+            NodeIndex::new(dst.index() + offset),
+            b[e].clone(),
+        );
+    }
+}
+
+/// Insert the pairs of `m` into `union`, shifting sources by `src` and
+/// targets by `dst`, each a (node, edge) offset.
+fn shift_into(union: &mut Morphism, m: &Morphism, src: (usize, usize), dst: (usize, usize)) {
+    for (s, t) in &m.node_map {
+        union.insert_node(NodeIndex::new(s.index() + src.0), NodeIndex::new(t.index() + dst.0));
+    }
+    for (s, t) in &m.edge_map {
+        union.insert_edge(EdgeIndex::new(s.index() + src.1), EdgeIndex::new(t.index() + dst.1));
+    }
+}
+
+/// Combine `f: A → B` and `g: A' → B'` into `A + A' → B + B'`, where `src`
+/// and `dst` are the first summands A and B that shift the indices of `g`.
+fn union_morphism<N, E>(src: &Graph<N, E>, dst: &Graph<N, E>, f: &Morphism, g: &Morphism) -> Morphism {
+    let mut union = f.clone();
+    shift_into(&mut union, g, (src.node_count(), src.edge_count()), (dst.node_count(), dst.edge_count()));
+    union
+}
+
+/// The identity morphism on `g`.
+fn identity<N, E>(g: &Graph<N, E>) -> Morphism {
+    let mut id = Morphism::new();
+    for n in g.node_indices() {
+        id.insert_node(n, n);
+    }
+    for e in g.edge_indices() {
+        id.insert_edge(e, e);
+    }
+    id
+}
+
+/// Find a node hit by two different sources of `m`, if any.
+fn duplicate_image(m: &Morphism) -> Option<NodeIndex> {
+    let mut images: Vec<_> = m.node_map.values().copied().collect();
+    images.sort_unstable();
+    images.windows(2).find(|w| w[0] == w[1]).map(|w| w[0])
+}
+
+/// Check that `m` maps every node of `src` to a node of `dst`, and edges of
+/// `src` to edges of `dst` whose endpoints are the images of the source endpoints.
+pub(crate) fn validate_morphism<N, E>(
+    name: &'static str,
+    src: &Graph<N, E>,
+    dst: &Graph<N, E>,
+    m: &Morphism,
+) -> Result<(), RuleError> {
+    // Node mappings: source in `src`, target in `dst`
+    let mut nodes: Vec<_> = m.node_map.iter().collect();
+    nodes.sort_unstable();
+    for (&s_node, &d_node) in nodes {
+        if src.node_weight(s_node).is_none() || dst.node_weight(d_node).is_none() {
+            return Err(RuleError::DanglingNode { morphism: name, node: s_node });
+        }
+    }
+    if let Some(node) = src.node_indices().find(|n| !m.node_map.contains_key(n)) {
+        return Err(RuleError::NotTotal { morphism: name, node });
+    }
+    // Edge mappings: domain edges map and endpoints preserved
+    let mut edges: Vec<_> = m.edge_map.iter().collect();
+    edges.sort_unstable();
+    for (&s_edge, &d_edge) in edges {
+        let (Some((s_src, s_dst)), Some((d_src, d_dst))) = (
+            src.edge_endpoints(s_edge),
+            dst.edge_endpoints(d_edge)
+        ) else {
+            return Err(RuleError::DanglingEdge { morphism: name, edge: s_edge });
+        };
+        if m.node_map.get(&s_src) != Some(&d_src) || m.node_map.get(&s_dst) != Some(&d_dst) {
+            return Err(RuleError::EdgeEndpointMismatch { morphism: name, edge: s_edge });
         }
-        true
     }
+    Ok(())
 }
 
 impl<N, E> Rule<N, E>
@@ -98,11 +415,19 @@ where
     N: Clone + Display,
     E: Clone + Display,
 {
-    pub fn to_cypher(&self) -> String {
-        assert!(self.validate(), "DPO rule validation failed");
+    /// Export the rule as a Cypher query.
+    ///
+    /// # Errors
+    /// Returns the `RuleError` if the rule is not a valid DPO rule.
+    pub fn to_cypher(&self) -> Result<String, RuleError> {
+        self.validate()?;
 
         // 1) LHS node variable (only one in your example)
-        let lhs_node = self.lhs.node_indices().next().unwrap();
+        let lhs_node = self
+            .lhs
+            .node_indices()
+            .next()
+            .ok_or(RuleError::MissingComponent("LHS node"))?;
         let n0 = format!("n{}", lhs_node.index());
         let name0 = format!("{}", self.lhs.node_weight(lhs_node).unwrap());
 
@@ -155,7 +480,7 @@ where
             cy.push('\n');
         }
 
-        cy
+        Ok(cy)
     }
 }
 
@@ -166,7 +491,8 @@ use std::path::Path;
 /// Write the given rule’s Cypher export to the specified file path.
 ///  
 /// # Errors
-/// Returns any I/O error encountered while creating or writing the file.
+/// Returns any I/O error encountered while creating or writing the file,
+/// or an `InvalidInput` error wrapping the `RuleError` of an invalid rule.
 pub fn write_cypher_to_file<N, E>(
     rule: &Rule<N, E>,
     path: &Path,
@@ -175,7 +501,9 @@ where
     N: Clone + Display,
     E: Clone + Display,
 {
-    let cy = rule.to_cypher();
+    let cy = rule
+        .to_cypher()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
     let mut f = File::create(path)?;
     f.write_all(cy.as_bytes())
 }
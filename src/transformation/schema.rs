@@ -0,0 +1,261 @@
+use petgraph::graph::Graph;
+use std::collections::HashMap;
+use crate::algorithms::vf2::find_mappings_by;
+use crate::transformation::{Rule, Nac, Morphism, MergePolicy, RuleError, RewriteError};
+use crate::transformation::dpo::{apply_once, satisfies_nacs, Derivation};
+
+/// A label inside a rule schema: either a concrete value or a named variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Label<T> {
+    Const(T),
+    Var(String),
+}
+
+/// A constraint on the values a label variable may bind to.
+#[derive(Debug, Clone)]
+pub enum Constraint<T> {
+    /// Any value is accepted.
+    Any,
+    /// The value must be one of the listed values.
+    OneOf(Vec<T>),
+    /// The value must pass a type test, e.g. `|s| s.parse::<i64>().is_ok()`.
+    Type(fn(&T) -> bool),
+}
+
+impl<T: PartialEq> Constraint<T> {
+    /// Check whether `value` satisfies this constraint.
+    pub fn allows(&self, value: &T) -> bool {
+        match self {
+            Constraint::Any => true,
+            Constraint::OneOf(values) => values.contains(value),
+            Constraint::Type(test) => test(value),
+        }
+    }
+}
+
+/// Values bound to the variables of a schema during matching.
+///
+/// Node and edge variables live in separate namespaces since they bind
+/// values of different types.
+#[derive(Debug, Clone)]
+pub struct Binding<N, E> {
+    pub nodes: HashMap<String, N>,
+    pub edges: HashMap<String, E>,
+}
+
+impl<N, E> Binding<N, E> {
+    /// Create an empty binding.
+    pub fn new() -> Self {
+        Binding {
+            nodes: HashMap::new(),
+            edges: HashMap::new(),
+        }
+    }
+}
+
+impl<N, E> Default for Binding<N, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A match of a schema's LHS together with the binding it induces.
+pub type SchemaMatch<N, E> = (Morphism, Binding<N, E>);
+
+/// A rule whose LHS and RHS labels may contain variables.
+///
+/// Variables bind while matching the LHS, consistently across every node
+/// (and every edge) they occur on, and are substituted into the RHS when
+/// the schema is instantiated into a concrete `Rule`.
+///
+/// The merge policy of the instantiated rule is taken from `merge_policy`,
+/// since the policy of `rule` combines labels rather than host weights.
+pub struct RuleSchema<N, E> {
+    pub rule: Rule<Label<N>, Label<E>>,
+    pub node_constraints: HashMap<String, Constraint<N>>,
+    pub edge_constraints: HashMap<String, Constraint<E>>,
+    pub merge_policy: MergePolicy<N>,
+}
+
+impl<N, E> RuleSchema<N, E>
+where
+    N: Eq + Clone,
+    E: Eq + Clone,
+{
+    /// Wrap a rule over labels into a schema without constraints.
+    pub fn new(rule: Rule<Label<N>, Label<E>>) -> Self {
+        RuleSchema {
+            rule,
+            node_constraints: HashMap::new(),
+            edge_constraints: HashMap::new(),
+            merge_policy: MergePolicy::Rhs,
+        }
+    }
+
+    /// Restrict the values the node variable `var` may bind to.
+    pub fn constrain_node(mut self, var: &str, constraint: Constraint<N>) -> Self {
+        self.node_constraints.insert(var.to_string(), constraint);
+        self
+    }
+
+    /// Restrict the values the edge variable `var` may bind to.
+    pub fn constrain_edge(mut self, var: &str, constraint: Constraint<E>) -> Self {
+        self.edge_constraints.insert(var.to_string(), constraint);
+        self
+    }
+
+    /// Find all matches of the schema's LHS in `host` together with the
+    /// variable binding each match induces.
+    ///
+    /// # Errors
+    /// Returns the `RuleError` if the underlying rule is malformed.
+    pub fn find_matches(
+        &self,
+        host: &Graph<N, E>,
+        check_edge_labels: bool,
+    ) -> Result<Vec<SchemaMatch<N, E>>, RuleError> {
+        self.rule.validate_sqpo()?;
+
+        let node_match = |label: &Label<N>, value: &N| match label {
+            Label::Const(c) => c == value,
+            Label::Var(x) => self.node_constraints.get(x).is_none_or(|c| c.allows(value)),
+        };
+        let edge_match = |label: &Label<E>, value: &E| match label {
+            Label::Const(c) => c == value,
+            Label::Var(x) => self.edge_constraints.get(x).is_none_or(|c| c.allows(value)),
+        };
+
+        Ok(find_mappings_by(&self.rule.lhs, host, node_match, edge_match, check_edge_labels)
+            .into_iter()
+            .filter_map(|node_map| {
+                let mut m = Morphism::new();
+                for (p_node, h_node) in node_map {
+                    m.insert_node(p_node, h_node);
+                }
+                self.bind(host, &m).map(|b| (m, b))
+            })
+            .filter(|(m, b)| {
+                // NACs are checked on the rule instantiated with the binding;
+                // a NAC with variables the LHS does not bind never applies
+                self.rule.nacs.is_empty()
+                    || self
+                        .instantiate(b)
+                        .is_ok_and(|rule| satisfies_nacs(&rule, host, m, check_edge_labels))
+            })
+            .collect())
+    }
+
+    /// Compute the binding induced by match `m`, or `None` if a variable
+    /// would have to bind two different values.
+    fn bind(&self, host: &Graph<N, E>, m: &Morphism) -> Option<Binding<N, E>> {
+        let mut binding = Binding::new();
+        for l_node in self.rule.lhs.node_indices() {
+            if let Label::Var(x) = &self.rule.lhs[l_node] {
+                let value = &host[*m.map_node(&l_node)?];
+                if !self.node_constraints.get(x).is_none_or(|c| c.allows(value)) {
+                    return None;
+                }
+                match binding.nodes.get(x) {
+                    Some(bound) if bound != value => return None,
+                    Some(_) => {}
+                    None => {
+                        binding.nodes.insert(x.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        for l_edge in self.rule.lhs.edge_indices() {
+            if let Label::Var(x) = &self.rule.lhs[l_edge] {
+                let (l_src, l_dst) = self.rule.lhs.edge_endpoints(l_edge)?;
+                let h_edge = host.find_edge(*m.map_node(&l_src)?, *m.map_node(&l_dst)?)?;
+                let value = &host[h_edge];
+                if !self.edge_constraints.get(x).is_none_or(|c| c.allows(value)) {
+                    return None;
+                }
+                match binding.edges.get(x) {
+                    Some(bound) if bound != value => return None,
+                    Some(_) => {}
+                    None => {
+                        binding.edges.insert(x.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        Some(binding)
+    }
+
+    /// Substitute `binding` into the schema, producing a concrete rule.
+    ///
+    /// Node and edge indices are preserved, so a match found by
+    /// `find_matches` is also a match of the instantiated rule.
+    ///
+    /// # Errors
+    /// Returns `RuleError::UnboundVariable` if any variable is left unbound.
+    pub fn instantiate(&self, binding: &Binding<N, E>) -> Result<Rule<N, E>, RuleError> {
+        Ok(Rule {
+            lhs: substitute(&self.rule.lhs, binding)?,
+            interface: substitute(&self.rule.interface, binding)?,
+            rhs: substitute(&self.rule.rhs, binding)?,
+            k2l: self.rule.k2l.clone(),
+            k2r: self.rule.k2r.clone(),
+            merge_policy: self.merge_policy.clone(),
+            nacs: self
+                .rule
+                .nacs
+                .iter()
+                .map(|nac| {
+                    Ok(Nac {
+                        graph: substitute(&nac.graph, binding)?,
+                        l2n: nac.l2n.clone(),
+                    })
+                })
+                .collect::<Result<_, RuleError>>()?,
+        })
+    }
+
+    /// Apply the schema once at match `m` under `binding`.
+    ///
+    /// # Errors
+    /// Fails if a variable is unbound, the rule is malformed or gluing fails.
+    pub fn apply_once(
+        &self,
+        host: &Graph<N, E>,
+        m: &Morphism,
+        binding: &Binding<N, E>,
+    ) -> Result<Derivation<N, E>, RewriteError> {
+        let rule = self.instantiate(binding)?;
+        apply_once(&rule, host, m)
+    }
+}
+
+/// Replace every label in `graph` by its value under `binding`.
+fn substitute<N: Clone, E: Clone>(
+    graph: &Graph<Label<N>, Label<E>>,
+    binding: &Binding<N, E>,
+) -> Result<Graph<N, E>, RuleError> {
+    let mut out = Graph::with_capacity(graph.node_count(), graph.edge_count());
+    for node in graph.node_indices() {
+        let weight = match &graph[node] {
+            Label::Const(c) => c.clone(),
+            Label::Var(x) => binding
+                .nodes
+                .get(x)
+                .ok_or_else(|| RuleError::UnboundVariable(x.clone()))?
+                .clone(),
+        };
+        out.add_node(weight);
+    }
+    for edge in graph.edge_indices() {
+        let (src, dst) = graph.edge_endpoints(edge).unwrap();
+        let weight = match &graph[edge] {
+            Label::Const(c) => c.clone(),
+            Label::Var(x) => binding
+                .edges
+                .get(x)
+                .ok_or_else(|| RuleError::UnboundVariable(x.clone()))?
+                .clone(),
+        };
+        out.add_edge(src, dst, weight);
+    }
+    Ok(out)
+}
@@ -0,0 +1,71 @@
+use petgraph::graph::Graph;
+use crate::transformation::{Rule, Morphism, RewriteError};
+use crate::transformation::dpo::{Comatch, Derivation};
+use crate::transformation::util::{check_match, delete_part, add_part, Tracker};
+
+/// Apply a single SPO rewrite at the given match morphism, in place.
+/// Returns the comatch R → host.
+///
+/// Unlike `dpo::apply_once_mut`, there is no gluing condition: deleting a
+/// node also deletes every host edge incident to it, including edges outside
+/// the image of the LHS. Matches come from `dpo::find_matches`.
+///
+/// # Errors
+/// Returns `RewriteError::InvalidRule` if the rule is malformed and
+/// `RewriteError::IncompleteMatch` if `m` does not map every LHS node into
+/// the host.
+pub fn apply_once_mut<N, E>(
+    host: &mut Graph<N, E>,
+    rule: &Rule<N, E>,
+    m: &Morphism,
+) -> Result<Comatch, RewriteError>
+where
+    N: Clone,
+    E: Clone,
+{
+    let mut tracker = Tracker::new(host);
+    rewrite_tracked(host, rule, m, &mut tracker)
+}
+
+/// Apply a single SPO rewrite at the given match morphism.
+/// Returns the rewritten copy of `host` together with the comatch and the
+/// tracking morphism; see `apply_once_mut`.
+///
+/// # Errors
+/// Returns `RewriteError::InvalidRule` if the rule is malformed and
+/// `RewriteError::IncompleteMatch` if `m` does not map every LHS node into
+/// the host.
+pub fn apply_once<N, E>(
+    rule: &Rule<N, E>,
+    host: &Graph<N, E>,
+    m: &Morphism,
+) -> Result<Derivation<N, E>, RewriteError>
+where
+    N: Clone,
+    E: Clone,
+{
+    let mut graph = host.clone();
+    let mut tracker = Tracker::new(host);
+    let comatch = rewrite_tracked(&mut graph, rule, m, &mut tracker)?;
+    Ok(Derivation { graph, comatch, tracking: tracker.morphism() })
+}
+
+/// SPO rewrite in place, recording removals in `tracker`.
+fn rewrite_tracked<N, E>(
+    host: &mut Graph<N, E>,
+    rule: &Rule<N, E>,
+    m: &Morphism,
+    tracker: &mut Tracker,
+) -> Result<Comatch, RewriteError>
+where
+    N: Clone,
+    E: Clone,
+{
+    // Validate rule consistency
+    rule.validate()?;
+    check_match(host, m, rule)?;
+
+    // Dangling edges are removed together with their nodes
+    let m = delete_part(host, m, rule, tracker);
+    Ok(add_part(host, &m.compose(&rule.k2l), rule, tracker))
+}
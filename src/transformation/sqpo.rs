@@ -0,0 +1,155 @@
+use petgraph::graph::{Graph, NodeIndex, EdgeIndex};
+use std::collections::{HashMap, HashSet};
+use crate::transformation::{Rule, Morphism, RewriteError};
+use crate::transformation::dpo::{Comatch, Derivation};
+use crate::transformation::util::{check_match, add_part, image, remove_nodes, Tracker};
+
+/// Apply a single SqPO (sesqui-pushout) rewrite at the given match morphism,
+/// in place. Returns the comatch R → host.
+///
+/// Interface nodes that `k2l` maps onto the same LHS node clone the matched
+/// host node: every copy receives all context edges of the original, i.e.
+/// the incident host edges not matched by the LHS. Deleting a node removes
+/// its dangling edges as in SPO. Matches come from `dpo::find_matches`.
+///
+/// # Errors
+/// Returns `RewriteError::InvalidRule` if the rule is malformed and
+/// `RewriteError::IncompleteMatch` if `m` does not map every LHS node into
+/// the host.
+pub fn apply_once_sqpo_mut<N, E>(
+    host: &mut Graph<N, E>,
+    rule: &Rule<N, E>,
+    m: &Morphism,
+) -> Result<Comatch, RewriteError>
+where
+    N: Clone,
+    E: Clone,
+{
+    let mut tracker = Tracker::new(host);
+    rewrite_tracked(host, rule, m, &mut tracker)
+}
+
+/// Apply a single SqPO rewrite at the given match morphism.
+/// Returns the rewritten copy of `host` together with the comatch and the
+/// tracking morphism, which maps a cloned node to its first copy;
+/// see `apply_once_sqpo_mut`.
+///
+/// # Errors
+/// Returns `RewriteError::InvalidRule` if the rule is malformed and
+/// `RewriteError::IncompleteMatch` if `m` does not map every LHS node into
+/// the host.
+pub fn apply_once_sqpo<N, E>(
+    rule: &Rule<N, E>,
+    host: &Graph<N, E>,
+    m: &Morphism,
+) -> Result<Derivation<N, E>, RewriteError>
+where
+    N: Clone,
+    E: Clone,
+{
+    let mut graph = host.clone();
+    let mut tracker = Tracker::new(host);
+    let comatch = rewrite_tracked(&mut graph, rule, m, &mut tracker)?;
+    Ok(Derivation { graph, comatch, tracking: tracker.morphism() })
+}
+
+/// SqPO rewrite in place, recording removals in `tracker`.
+fn rewrite_tracked<N, E>(
+    result: &mut Graph<N, E>,
+    rule: &Rule<N, E>,
+    m: &Morphism,
+    tracker: &mut Tracker,
+) -> Result<Comatch, RewriteError>
+where
+    N: Clone,
+    E: Clone,
+{
+    // Validate rule consistency, allowing a non-injective k2l
+    rule.validate_sqpo()?;
+    check_match(result, m, rule)?;
+    let host_edges = result.edge_count();
+
+    // 1. Assign a host node to every interface node. The first preimage of
+    //    an LHS node keeps the matched node, every further one gets a copy.
+    let mut k_match = Morphism::new();
+    let mut copies: HashMap<NodeIndex, Vec<NodeIndex>> = HashMap::new();
+    let mut k_nodes: Vec<_> = rule.k2l.node_map.iter().collect();
+    k_nodes.sort_unstable();
+    for (&k_node, l_node) in k_nodes {
+        let h_node = *m.map_node(l_node).unwrap();
+        let entry = copies.entry(h_node).or_default();
+        if entry.is_empty() {
+            entry.push(h_node);
+            k_match.insert_node(k_node, h_node);
+        } else {
+            let clone = result.add_node(result[h_node].clone());
+            entry.push(clone);
+            k_match.insert_node(k_node, clone);
+        }
+    }
+
+    // 2. Host edges matched by the LHS; these are rebuilt from K below.
+    let mut matched_edges: HashMap<EdgeIndex, EdgeIndex> = HashMap::new();
+    for l_edge in rule.lhs.edge_indices() {
+        let (l_src, l_dst) = rule.lhs.edge_endpoints(l_edge).unwrap();
+        if let Some(h_edge) = result.find_edge(*m.map_node(&l_src).unwrap(), *m.map_node(&l_dst).unwrap()) {
+            matched_edges.insert(l_edge, h_edge);
+        }
+    }
+    let matched: HashSet<EdgeIndex> = matched_edges.values().copied().collect();
+
+    // 3. Copy context edges of cloned nodes to every combination of copies.
+    for h_edge in (0..host_edges).map(EdgeIndex::new) {
+        if matched.contains(&h_edge) {
+            continue;
+        }
+        let (u, v) = result.edge_endpoints(h_edge).unwrap();
+        let single_u = vec![u];
+        let single_v = vec![v];
+        let us = copies.get(&u).filter(|c| c.len() > 1).unwrap_or(&single_u);
+        let vs = copies.get(&v).filter(|c| c.len() > 1).unwrap_or(&single_v);
+        for &cu in us {
+            for &cv in vs {
+                if (cu, cv) != (u, v) {
+                    let weight = result[h_edge].clone();
+                    result.add_edge(cu, cv, weight);
+                }
+            }
+        }
+    }
+
+    // 4. Re-create preserved LHS edges between the interface images.
+    let mut redirected: HashSet<EdgeIndex> = HashSet::new();
+    let mut k_edges: Vec<_> = rule.k2l.edge_map.iter().collect();
+    k_edges.sort_unstable();
+    for (&k_edge, l_edge) in k_edges {
+        if let Some(&h_edge) = matched_edges.get(l_edge) {
+            let (k_src, k_dst) = rule.interface.edge_endpoints(k_edge).unwrap();
+            let weight = result[h_edge].clone();
+            let new_edge = result.add_edge(k_match.node_map[&k_src], k_match.node_map[&k_dst], weight);
+            // The first copy of a preserved edge carries its identity
+            if !redirected.contains(&h_edge) {
+                redirected.insert(h_edge);
+                tracker.redirect_edge(h_edge, new_edge);
+            }
+        }
+    }
+
+    // 5. Delete all matched edges, then the nodes of L \ K with their dangling edges.
+    let mut edges: Vec<EdgeIndex> = matched.into_iter().collect();
+    edges.sort_unstable_by(|a, b| b.cmp(a));
+    for h_edge in edges {
+        tracker.remove_edge(result, h_edge);
+    }
+    let (kept_nodes, _) = image(&rule.k2l);
+    let to_delete = rule
+        .lhs
+        .node_indices()
+        .filter(|l| !kept_nodes.contains(l))
+        .filter_map(|l| m.map_node(&l).copied())
+        .collect();
+    remove_nodes(result, to_delete, &mut k_match, tracker);
+
+    // 6. Glue in R \ K.
+    Ok(add_part(result, &k_match, rule, tracker))
+}
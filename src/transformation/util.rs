@@ -1,22 +1,34 @@
-use petgraph::graph::{Graph, NodeIndex};
+use petgraph::graph::{Graph, NodeIndex, EdgeIndex};
 use petgraph::visit::EdgeRef;
-use crate::transformation::rule::Rule;
+use petgraph::Direction;
+use crate::transformation::rule::{Rule, MergePolicy};
 use crate::transformation::morphism::Morphism;
+use crate::transformation::error::RewriteError;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// Delete the image of L \ K from the host graph according to match morphism m.
 /// Removes edges in L not in interface and nodes in L not in interface.
-pub fn delete_part<N, E>(host: &mut Graph<N, E>, m: &Morphism, rule: &Rule<N, E>) {
-    // Prepare inverted l2k mapping
-    let inv_l2k = rule.l2k.invert();
+///
+/// Removing a node moves the host's last node into the freed index, so the
+/// match is returned with its node images updated to the surviving indices.
+/// Deleted nodes are dropped from the returned morphism.
+pub fn delete_part<N, E>(
+    host: &mut Graph<N, E>,
+    m: &Morphism,
+    rule: &Rule<N, E>,
+    tracker: &mut Tracker,
+) -> Morphism {
+    // LHS elements with a preimage in K are preserved
+    let (kept_nodes, kept_edges) = image(&rule.k2l);
 
     // 1. Delete edges that are in L but not in interface K
     for l_edge in rule.lhs.edge_indices() {
-        if inv_l2k.map_edge(&l_edge).is_none() {
+        if !kept_edges.contains(&l_edge) {
             let (l_src, l_dst) = rule.lhs.edge_endpoints(l_edge).unwrap();
-            if let (Some(&h_src), Some(&h_dst)) = (m.map_node(&l_src), m.map_node(&l_dst)) {
-                if let Some(e_idx) = host.find_edge(h_src, h_dst) {
-                    host.remove_edge(e_idx);
-                }
+            if let (Some(&h_src), Some(&h_dst)) = (m.map_node(&l_src), m.map_node(&l_dst))
+                && let Some(e_idx) = host.find_edge(h_src, h_dst)
+            {
+                tracker.remove_edge(host, e_idx);
             }
         }
     }
@@ -24,78 +36,359 @@ pub fn delete_part<N, E>(host: &mut Graph<N, E>, m: &Morphism, rule: &Rule<N, E>
     // 2. Delete nodes in L not in K (and their incident edges)
     let mut to_delete = Vec::new();
     for l_node in rule.lhs.node_indices() {
-        if inv_l2k.map_node(&l_node).is_none() {
-            if let Some(&h_node) = m.map_node(&l_node) {
-                to_delete.push(h_node);
-            }
+        if !kept_nodes.contains(&l_node)
+            && let Some(&h_node) = m.map_node(&l_node)
+        {
+            to_delete.push(h_node);
         }
     }
-    for node in to_delete {
-        host.remove_node(node);
+    let mut remaining = m.clone();
+    remaining.edge_map.clear();
+    remove_nodes(host, to_delete, &mut remaining, tracker);
+    remaining
+}
+
+/// Remove `nodes` (and their incident edges) from `host`, keeping the node
+/// images of `track` pointing at the right nodes.
+///
+/// Removing a node moves the host's last node into the freed index, so
+/// images are rewritten accordingly; images of removed nodes are dropped.
+pub fn remove_nodes<N, E>(
+    host: &mut Graph<N, E>,
+    mut nodes: Vec<NodeIndex>,
+    track: &mut Morphism,
+    tracker: &mut Tracker,
+) {
+    // Remove from the highest index down, so a node still pending deletion
+    // is never the one being moved into a freed slot.
+    nodes.sort_unstable_by(|a, b| b.cmp(a));
+    nodes.dedup();
+    track.node_map.retain(|_, h| !nodes.contains(h));
+    for node in nodes {
+        tracker.remove_node(host, node);
+        let moved = NodeIndex::new(host.node_count());
+        for h in track.node_map.values_mut() {
+            if *h == moved {
+                *h = node;
+            }
+        }
     }
 }
 
-/// Add the image of R \ K into the host graph according to match morphism m.
-/// Adds nodes and edges from R not in interface, connecting via interface mapping.
-pub fn add_part<N: Clone, E: Clone>(host: &mut Graph<N, E>, m: &Morphism, rule: &Rule<N, E>) {
-    // Prepare inverted morphisms once
-    let inv_k2r = rule.k2r.invert();
-    let inv_l2k = rule.l2k.invert();
+/// Add the image of R \ K into the host graph.
+/// Adds nodes and edges from R not in interface, connecting them to the host
+/// nodes that `k_match` assigns to the interface (K → host, current indices).
+/// Host nodes whose interface nodes share an RHS node are merged first.
+/// Returns the comatch R → host.
+pub fn add_part<N: Clone, E: Clone>(
+    host: &mut Graph<N, E>,
+    k_match: &Morphism,
+    rule: &Rule<N, E>,
+    tracker: &mut Tracker,
+) -> Morphism {
+    let k_match = &merge_part(host, k_match, rule, tracker);
+    let mut comatch = Morphism::new();
+
+    // Interface nodes are already in the host
+    for (k_node, &r_node) in &rule.k2r.node_map {
+        comatch.insert_node(r_node, *k_match.map_node(k_node).unwrap());
+    }
 
     // First, add new nodes: those in R not in interface
-    let mut k_to_new: Vec<(NodeIndex, NodeIndex)> = Vec::new();
     for r_node in rule.rhs.node_indices() {
-        if inv_k2r.map_node(&r_node).is_none() {
+        if comatch.map_node(&r_node).is_none() {
             let weight = rule.rhs.node_weight(r_node).unwrap().clone();
             let new_node = host.add_node(weight);
-            k_to_new.push((r_node, new_node));
+            comatch.insert_node(r_node, new_node);
         }
     }
 
     // Next, add edges: those in R not in interface
+    let (_, kept_edges) = image(&rule.k2r);
     for r_edge in rule.rhs.edge_indices() {
-        if inv_k2r.map_edge(&r_edge).is_none() {
-            let (r_src, r_dst) = rule.rhs.edge_endpoints(r_edge).unwrap();
-            // Determine host source
-            let h_src = if let Some(&k_src) = inv_k2r.map_node(&r_src) {
-                let via_l = inv_l2k.map_node(&k_src).unwrap();
-                *m.map_node(via_l).unwrap()
-            } else {
-                k_to_new.iter().find(|&&(r,_)| r == r_src).unwrap().1
-            };
-            // Determine host target
-            let h_dst = if let Some(&k_dst) = inv_k2r.map_node(&r_dst) {
-                let via_l = inv_l2k.map_node(&k_dst).unwrap();
-                *m.map_node(via_l).unwrap()
-            } else {
-                k_to_new.iter().find(|&&(r,_)| r == r_dst).unwrap().1
-            };
+        let (r_src, r_dst) = rule.rhs.edge_endpoints(r_edge).unwrap();
+        let h_src = *comatch.map_node(&r_src).unwrap();
+        let h_dst = *comatch.map_node(&r_dst).unwrap();
+        if !kept_edges.contains(&r_edge) {
             let e_weight = rule.rhs.edge_weight(r_edge).unwrap().clone();
-            host.add_edge(h_src, h_dst, e_weight);
+            let new_edge = host.add_edge(h_src, h_dst, e_weight);
+            comatch.insert_edge(r_edge, new_edge);
+        } else if let Some(h_edge) = host.find_edge(h_src, h_dst) {
+            // Preserved edge: its host image runs between the same endpoints
+            comatch.insert_edge(r_edge, h_edge);
+        }
+    }
+    comatch
+}
+
+/// Merge host nodes whose interface nodes `k2r` maps onto the same RHS node.
+/// Nodes and edges that are preserved without merging are relabelled under
+/// `MergePolicy::Rhs` and keep their weight otherwise.
+///
+/// The image of the lowest-indexed interface node survives; every edge
+/// incident to the other images is redirected to it before they are removed,
+/// and its weight is chosen by the rule's `merge_policy`. Interface edges
+/// mapped onto the same RHS edge are reduced to a single host edge.
+/// Returns `k_match` updated to the merged host indices.
+pub fn merge_part<N: Clone, E: Clone>(
+    host: &mut Graph<N, E>,
+    k_match: &Morphism,
+    rule: &Rule<N, E>,
+    tracker: &mut Tracker,
+) -> Morphism {
+    let mut k_match = k_match.clone();
+    let mut groups: BTreeMap<NodeIndex, Vec<NodeIndex>> = BTreeMap::new();
+    for (&k_node, &r_node) in &rule.k2r.node_map {
+        groups.entry(r_node).or_default().push(k_node);
+    }
+
+    for (r_node, mut k_nodes) in groups {
+        if k_nodes.len() < 2 {
+            // A preserved node takes its RHS label under `MergePolicy::Rhs`
+            if let MergePolicy::Rhs = rule.merge_policy {
+                host[k_match.node_map[&k_nodes[0]]] = rule.rhs[r_node].clone();
+            }
+            continue;
+        }
+        k_nodes.sort_unstable();
+        let mut merged: Vec<NodeIndex> = Vec::new();
+        for k in &k_nodes {
+            let h = k_match.node_map[k];
+            if !merged.contains(&h) {
+                merged.push(h);
+            }
+        }
+        let survivor = merged[0];
+        let weight = match rule.merge_policy {
+            MergePolicy::Rhs => rule.rhs[r_node].clone(),
+            MergePolicy::Survivor => host[survivor].clone(),
+            MergePolicy::Combine(f) => merged[1..]
+                .iter()
+                .fold(host[survivor].clone(), |acc, &h| f(&acc, &host[h])),
+        };
+        host[survivor] = weight;
+
+        // Redirect every edge touching a merged node onto the survivor
+        let redirect = |n: NodeIndex| if merged.contains(&n) { survivor } else { n };
+        let mut incident: Vec<EdgeIndex> = Vec::new();
+        for &h in &merged[1..] {
+            for dir in [Direction::Outgoing, Direction::Incoming] {
+                for edge in host.edges_directed(h, dir) {
+                    if !incident.contains(&edge.id()) {
+                        incident.push(edge.id());
+                    }
+                }
+            }
+        }
+        for edge in incident {
+            let (src, dst) = host.edge_endpoints(edge).unwrap();
+            let weight = host[edge].clone();
+            let new_edge = host.add_edge(redirect(src), redirect(dst), weight);
+            tracker.redirect_edge(edge, new_edge);
+        }
+        for &h in &merged[1..] {
+            tracker.redirect_node(h, survivor);
+        }
+        for k in &k_nodes {
+            k_match.insert_node(*k, survivor);
+        }
+        remove_nodes(host, merged[1..].to_vec(), &mut k_match, tracker);
+    }
+
+    // Interface edges identified in R now run in parallel; keep one of them
+    let mut edge_groups: HashMap<EdgeIndex, usize> = HashMap::new();
+    for &r_edge in rule.k2r.edge_map.values() {
+        *edge_groups.entry(r_edge).or_default() += 1;
+    }
+    for (k_edge, r_edge) in &rule.k2r.edge_map {
+        if edge_groups[r_edge] > 1 {
+            let (k_src, k_dst) = rule.interface.edge_endpoints(*k_edge).unwrap();
+            let (h_src, h_dst) = (k_match.node_map[&k_src], k_match.node_map[&k_dst]);
+            if let Some(edge) = host.find_edge(h_src, h_dst) {
+                tracker.remove_edge(host, edge);
+            }
+            *edge_groups.get_mut(r_edge).unwrap() -= 1;
+        }
+    }
+
+    // A preserved edge takes its RHS label under `MergePolicy::Rhs`
+    if let MergePolicy::Rhs = rule.merge_policy {
+        for (k_edge, r_edge) in &rule.k2r.edge_map {
+            let (k_src, k_dst) = rule.interface.edge_endpoints(*k_edge).unwrap();
+            if let Some(edge) = host.find_edge(k_match.node_map[&k_src], k_match.node_map[&k_dst]) {
+                host[edge] = rule.rhs[*r_edge].clone();
+            }
+        }
+    }
+    k_match
+}
+
+/// Follows the elements of a host graph through a rewrite.
+///
+/// `Graph` removals move the last node or edge into the freed index, so
+/// every removal and redirection must go through the tracker. Only touched
+/// indices are recorded; everything else is implicitly mapped to itself.
+#[derive(Debug, Clone)]
+pub struct Tracker {
+    nodes: IndexTrack,
+    edges: IndexTrack,
+}
+
+impl Tracker {
+    /// Start tracking `host` in its current state.
+    pub fn new<N, E>(host: &Graph<N, E>) -> Self {
+        Tracker {
+            nodes: IndexTrack::new(host.node_count()),
+            edges: IndexTrack::new(host.edge_count()),
+        }
+    }
+
+    /// Remove `edge` from `host`, recording the index shift.
+    pub fn remove_edge<N, E>(&mut self, host: &mut Graph<N, E>, edge: EdgeIndex) {
+        let last = host.edge_count() - 1;
+        host.remove_edge(edge);
+        self.edges.remove(edge.index(), last);
+    }
+
+    /// Remove `node` and its incident edges from `host`, recording the index shifts.
+    pub fn remove_node<N, E>(&mut self, host: &mut Graph<N, E>, node: NodeIndex) {
+        loop {
+            let edge = host
+                .edges_directed(node, Direction::Outgoing)
+                .chain(host.edges_directed(node, Direction::Incoming))
+                .map(|e| e.id())
+                .next();
+            match edge {
+                Some(edge) => self.remove_edge(host, edge),
+                None => break,
+            }
+        }
+        let last = host.node_count() - 1;
+        host.remove_node(node);
+        self.nodes.remove(node.index(), last);
+    }
+
+    /// Record that the elements currently tracked at node `from` now live at `to`.
+    pub fn redirect_node(&mut self, from: NodeIndex, to: NodeIndex) {
+        self.nodes.redirect(from.index(), to.index());
+    }
+
+    /// Record that the elements currently tracked at edge `from` now live at `to`.
+    pub fn redirect_edge(&mut self, from: EdgeIndex, to: EdgeIndex) {
+        self.edges.redirect(from.index(), to.index());
+    }
+
+    /// The partial morphism from the tracked graph to its current state.
+    pub fn morphism(&self) -> Morphism {
+        let mut m = Morphism::new();
+        for (orig, cur) in self.nodes.pairs() {
+            m.insert_node(NodeIndex::new(orig), NodeIndex::new(cur));
+        }
+        for (orig, cur) in self.edges.pairs() {
+            m.insert_edge(EdgeIndex::new(orig), EdgeIndex::new(cur));
+        }
+        m
+    }
+}
+
+/// Origin/current bookkeeping for one kind of index.
+#[derive(Debug, Clone)]
+struct IndexTrack {
+    /// Number of elements when tracking started.
+    len: usize,
+    /// Original index → current index, `None` once removed.
+    forward: HashMap<usize, Option<usize>>,
+    /// Current index → original indices living there.
+    origins: HashMap<usize, Vec<usize>>,
+}
+
+impl IndexTrack {
+    fn new(len: usize) -> Self {
+        IndexTrack { len, forward: HashMap::new(), origins: HashMap::new() }
+    }
+
+    /// Take the original indices living at `cur`, leaving the slot empty.
+    fn take(&mut self, cur: usize) -> Vec<usize> {
+        let len = self.len;
+        let origins = self.origins.insert(cur, Vec::new());
+        origins.unwrap_or_else(|| if cur < len { vec![cur] } else { Vec::new() })
+    }
+
+    /// Record the swap-removal of `cur` while `last` was the highest index.
+    fn remove(&mut self, cur: usize, last: usize) {
+        for orig in self.take(cur) {
+            self.forward.insert(orig, None);
+        }
+        if cur != last {
+            let moved = self.take(last);
+            for &orig in &moved {
+                self.forward.insert(orig, Some(cur));
+            }
+            self.origins.insert(cur, moved);
         }
     }
+
+    fn redirect(&mut self, from: usize, to: usize) {
+        let moved = self.take(from);
+        let mut target = self.take(to);
+        for &orig in &moved {
+            self.forward.insert(orig, Some(to));
+        }
+        target.extend(moved);
+        self.origins.insert(to, target);
+    }
+
+    fn pairs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.len).filter_map(|orig| match self.forward.get(&orig) {
+            Some(cur) => cur.map(|c| (orig, c)),
+            None => Some((orig, orig)),
+        })
+    }
+}
+
+/// Collect the node and edge images of a morphism.
+pub fn image(m: &Morphism) -> (HashSet<NodeIndex>, HashSet<EdgeIndex>) {
+    (
+        m.node_map.values().copied().collect(),
+        m.edge_map.values().copied().collect(),
+    )
+}
+
+/// Check that `m` maps every LHS node to a node of `host`.
+pub fn check_match<N, E>(host: &Graph<N, E>, m: &Morphism, rule: &Rule<N, E>) -> Result<(), RewriteError> {
+    match rule.lhs.node_indices().find(|l| m.map_node(l).is_none_or(|h| host.node_weight(*h).is_none())) {
+        Some(node) => Err(RewriteError::IncompleteMatch { node }),
+        None => Ok(()),
+    }
 }
 
 /// Check the gluing condition: ensure deleting L\K does not leave dangling edges in the host.
+///
+/// Every host edge at a deleted node must be the image of an LHS edge.
+/// Edges are counted per pair of endpoints, so a parallel edge that the LHS
+/// does not match is caught whatever the order of the host's edges.
 pub fn check_gluing<N, E>(host: &Graph<N, E>, m: &Morphism, rule: &Rule<N, E>) -> bool {
+    let (kept_nodes, _) = image(&rule.k2l);
+    let mut matched: HashMap<(NodeIndex, NodeIndex), usize> = HashMap::new();
+    for l_edge in rule.lhs.edge_indices() {
+        let (l_src, l_dst) = rule.lhs.edge_endpoints(l_edge).unwrap();
+        if let (Some(&h_src), Some(&h_dst)) = (m.map_node(&l_src), m.map_node(&l_dst)) {
+            *matched.entry((h_src, h_dst)).or_default() += 1;
+        }
+    }
+    let mut incident: HashSet<EdgeIndex> = HashSet::new();
     for l_node in rule.lhs.node_indices() {
-        if rule.l2k.map_node(&l_node).is_none() {
-            if let Some(&h_node) = m.map_node(&l_node) {
-                for edge in host.edges(h_node) {
-                    let other = if edge.source() == h_node {
-                        edge.target()
-                    } else {
-                        edge.source()
-                    };
-                    let valid = rule.lhs.node_indices().any(|l2|
-                        m.map_node(&l2).map(|&n| n == other).unwrap_or(false)
-                    );
-                    if !valid {
-                        return false;
-                    }
-                }
+        if !kept_nodes.contains(&l_node)
+            && let Some(&h_node) = m.map_node(&l_node)
+        {
+            for dir in [Direction::Outgoing, Direction::Incoming] {
+                incident.extend(host.edges_directed(h_node, dir).map(|e| e.id()));
             }
         }
     }
-    true
+    let mut dangling: HashMap<(NodeIndex, NodeIndex), usize> = HashMap::new();
+    for edge in incident {
+        *dangling.entry(host.edge_endpoints(edge).unwrap()).or_default() += 1;
+    }
+    dangling.iter().all(|(ends, n)| matched.get(ends).is_some_and(|k| k >= n))
 }
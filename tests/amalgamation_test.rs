@@ -0,0 +1,130 @@
+// tests/amalgamation_test.rs
+
+use pushout::{Rule, Morphism, InteractionScheme, RewriteError};
+use petgraph::graph::{Graph, NodeIndex};
+
+/// Delete an X together with all of its outgoing `owns` edges.
+fn delete_with_owned() -> InteractionScheme<&'static str, &'static str> {
+    let mut k_lhs = Graph::<&str, &str>::new();
+    let kx = k_lhs.add_node("X");
+    let kernel = Rule::new(k_lhs, Graph::new(), Graph::new(), Morphism::new(), Morphism::new());
+
+    // Multi-rule: X -owns-> Item  =>  Item
+    let mut lhs = Graph::<&str, &str>::new();
+    let lx = lhs.add_node("X");
+    let li = lhs.add_node("Item");
+    lhs.add_edge(lx, li, "owns");
+    let mut interface = Graph::<&str, &str>::new();
+    let ki = interface.add_node("Item");
+    let mut rhs = Graph::<&str, &str>::new();
+    let ri = rhs.add_node("Item");
+    let mut l2k = Morphism::new();
+    l2k.insert_node(li, ki);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(ki, ri);
+    let multi = Rule::new(lhs, interface, rhs, l2k, k2r);
+
+    let mut embed_lhs = Morphism::new();
+    embed_lhs.insert_node(kx, lx);
+    InteractionScheme::new(kernel).with_multi_rule(multi, embed_lhs, Morphism::new(), Morphism::new())
+}
+
+#[test]
+fn test_kernel_and_all_extensions_apply_in_one_step() {
+    // Host: I1 <-owns- X -owns-> I2, plus a loose I3
+    let mut host = Graph::<&str, &str>::new();
+    let x = host.add_node("X");
+    let i1 = host.add_node("Item");
+    let i2 = host.add_node("Item");
+    host.add_node("Item");
+    host.add_edge(x, i1, "owns");
+    host.add_edge(x, i2, "owns");
+
+    let scheme = delete_with_owned();
+    let matches = scheme.find_matches(&host).unwrap();
+    assert_eq!(matches.len(), 1);
+
+    let (rule, _) = scheme.amalgamate(&host, &matches[0]).unwrap();
+    assert_eq!(rule.lhs.node_count(), 3, "kernel X plus two owned items");
+
+    let result = scheme.apply_once(&host, &matches[0]).unwrap().graph;
+    assert_eq!(result.node_count(), 3);
+    assert_eq!(result.edge_count(), 0);
+    assert!(result.node_weights().all(|&w| w == "Item"));
+}
+
+#[test]
+fn test_other_edges_still_block_deletion() {
+    // The unmatched `likes` edge blocks deletion whichever edge comes first
+    for labels in [["owns", "likes"], ["likes", "owns"]] {
+        let mut host = Graph::<&str, &str>::new();
+        let x = host.add_node("X");
+        let i = host.add_node("Item");
+        for label in labels {
+            host.add_edge(x, i, label);
+        }
+
+        let scheme = delete_with_owned();
+        let m = &scheme.find_matches(&host).unwrap()[0];
+        assert_eq!(scheme.apply_once(&host, m).unwrap_err(), RewriteError::DanglingCondition);
+    }
+}
+
+/// Keep `lhs` and attach a new `mark` node to its node `at`; the kernel
+/// node X is node 0 of `lhs`.
+fn mark_at(lhs: Graph<&'static str, &'static str>, at: usize, mark: &'static str) -> (Rule<&'static str, &'static str>, Morphism) {
+    let mut rhs = lhs.clone();
+    let new = rhs.add_node(mark);
+    rhs.add_edge(NodeIndex::new(at), new, "mark");
+    let mut l2k = Morphism::new();
+    let mut k2r = Morphism::new();
+    for n in lhs.node_indices() {
+        l2k.insert_node(n, n);
+        k2r.insert_node(n, n);
+    }
+    for e in lhs.edge_indices() {
+        l2k.insert_edge(e, e);
+        k2r.insert_edge(e, e);
+    }
+    let mut embed = Morphism::new();
+    embed.insert_node(0.into(), 0.into());
+    (Rule::new(lhs.clone(), lhs, rhs, l2k, k2r), embed)
+}
+
+#[test]
+fn test_multi_rules_sharing_a_preserved_node_both_fire() {
+    // Kernel: keep an X
+    let mut x = Graph::<&str, &str>::new();
+    x.add_node("X");
+    let mut id = Morphism::new();
+    id.insert_node(0.into(), 0.into());
+    let kernel = Rule::new(x.clone(), x.clone(), x, id.clone(), id);
+
+    // X -owns-> I marks I with a Tag; X -likes-> K -in-> I marks I with a Note
+    let mut owns = Graph::<&str, &str>::new();
+    let (ox, oi) = (owns.add_node("X"), owns.add_node("Item"));
+    owns.add_edge(ox, oi, "owns");
+    let mut likes = Graph::<&str, &str>::new();
+    let (lx, lk, li) = (likes.add_node("X"), likes.add_node("Item"), likes.add_node("Item"));
+    likes.add_edge(lx, lk, "likes");
+    likes.add_edge(lk, li, "in");
+    let (tag, embed_tag) = mark_at(owns, 1, "Tag");
+    let (note, embed_note) = mark_at(likes, 2, "Note");
+    let scheme = InteractionScheme::new(kernel)
+        .with_multi_rule(tag, embed_tag.clone(), embed_tag.clone(), embed_tag)
+        .with_multi_rule(note, embed_note.clone(), embed_note.clone(), embed_note);
+
+    // Host: X -owns-> I, X -likes-> K -in-> I
+    let mut host = Graph::<&str, &str>::new();
+    let (hx, hi, hk) = (host.add_node("X"), host.add_node("Item"), host.add_node("Item"));
+    host.add_edge(hx, hi, "owns");
+    host.add_edge(hx, hk, "likes");
+    host.add_edge(hk, hi, "in");
+
+    let m = &scheme.find_matches(&host).unwrap()[0];
+    let result = scheme.apply_once(&host, m).unwrap().graph;
+    let mut marks: Vec<_> = result.neighbors(hi).map(|n| result[n]).collect();
+    marks.sort_unstable();
+    assert_eq!(marks, ["Note", "Tag"]);
+    assert_eq!(result.node_count(), 5);
+}
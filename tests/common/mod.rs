@@ -0,0 +1,65 @@
+// tests/common/mod.rs
+//
+// Helpers shared by the integration tests; not every test uses all of them.
+#![allow(dead_code)]
+
+use pushout::{Rule, Morphism};
+use petgraph::graph::Graph;
+
+/// Relabel a `from` node to `to`.
+pub fn relabel(from: &'static str, to: &'static str) -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    let l = lhs.add_node(from);
+    let mut interface = Graph::<&str, &str>::new();
+    let k = interface.add_node(from);
+    let mut rhs = Graph::<&str, &str>::new();
+    let r = rhs.add_node(to);
+    let mut l2k = Morphism::new();
+    l2k.insert_node(l, k);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(k, r);
+    Rule::new(lhs, interface, rhs, l2k, k2r)
+}
+
+/// Relabel a `from` loop on an A node to `to`.
+pub fn relabel_loop(from: &'static str, to: &'static str) -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    let l = lhs.add_node("A");
+    let le = lhs.add_edge(l, l, from);
+    let mut interface = Graph::<&str, &str>::new();
+    let k = interface.add_node("A");
+    let ke = interface.add_edge(k, k, from);
+    let mut rhs = Graph::<&str, &str>::new();
+    let r = rhs.add_node("A");
+    let re = rhs.add_edge(r, r, to);
+    let mut l2k = Morphism::new();
+    l2k.insert_node(l, k);
+    l2k.insert_edge(le, ke);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(k, r);
+    k2r.insert_edge(ke, re);
+    Rule::new(lhs, interface, rhs, l2k, k2r)
+}
+
+/// `n` unconnected A nodes.
+pub fn row(n: usize) -> Graph<&'static str, &'static str> {
+    let mut g = Graph::new();
+    for _ in 0..n {
+        g.add_node("A");
+    }
+    g
+}
+
+/// Unconnected nodes with the given labels.
+pub fn nodes(labels: &[&'static str]) -> Graph<&'static str, &'static str> {
+    let mut g = Graph::new();
+    for l in labels {
+        g.add_node(*l);
+    }
+    g
+}
+
+/// The node labels of `g` in index order.
+pub fn labels(g: &Graph<&'static str, &'static str>) -> Vec<&'static str> {
+    g.node_weights().copied().collect()
+}
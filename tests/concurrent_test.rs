@@ -0,0 +1,78 @@
+// tests/concurrent_test.rs
+
+use pushout::{Rule, Morphism, RewriteError, find_matches, apply_once};
+use petgraph::graph::Graph;
+
+/// p1: create a node A.
+fn create_a() -> Rule<&'static str, &'static str> {
+    let mut rhs = Graph::<&str, &str>::new();
+    rhs.add_node("A");
+    Rule::new(Graph::new(), Graph::new(), rhs, Morphism::new(), Morphism::new())
+}
+
+/// p2: attach a fresh B to an existing A.
+fn extend_a() -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    let la = lhs.add_node("A");
+    let mut interface = Graph::<&str, &str>::new();
+    let ka = interface.add_node("A");
+    let mut rhs = Graph::<&str, &str>::new();
+    let ra = rhs.add_node("A");
+    let rb = rhs.add_node("B");
+    rhs.add_edge(ra, rb, "ab");
+
+    let mut l2k = Morphism::new();
+    l2k.insert_node(la, ka);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(ka, ra);
+    Rule::new(lhs, interface, rhs, l2k, k2r)
+}
+
+#[test]
+fn test_concurrent_rule_matches_sequential_application() {
+    let (p1, p2) = (create_a(), extend_a());
+    let overlaps = p1.dependency_overlaps(&p2);
+    assert_eq!(overlaps.len(), 1, "the created A is the only dependency");
+
+    let fused = p1.compose(&p2, overlaps[0].clone()).unwrap();
+    assert_eq!(fused.lhs.node_count(), 0);
+    assert_eq!(fused.rhs.node_count(), 2);
+    assert_eq!(fused.rhs.edge_count(), 1);
+
+    // Host: a lone C; both routes produce C, A -> B
+    let mut host = Graph::<&str, &str>::new();
+    host.add_node("C");
+    let m = &find_matches(&fused, &host, true).unwrap()[0];
+    let result = apply_once(&fused, &host, m).unwrap().graph;
+    assert_eq!(result.node_count(), 3);
+    assert_eq!(result.edge_count(), 1);
+}
+
+#[test]
+fn test_overlap_violating_gluing_is_rejected() {
+    // p2 deletes an A that has an incoming edge from C in L2,
+    // but p1 created A without that edge: E would leave it dangling
+    let mut lhs = Graph::<&str, &str>::new();
+    let la = lhs.add_node("A");
+    let lc = lhs.add_node("C");
+    lhs.add_edge(lc, la, "ca");
+    let mut interface = Graph::<&str, &str>::new();
+    let kc = interface.add_node("C");
+    let mut rhs = Graph::<&str, &str>::new();
+    let rc = rhs.add_node("C");
+    let mut l2k = Morphism::new();
+    l2k.insert_node(lc, kc);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(kc, rc);
+    let p2 = Rule::new(lhs, interface, rhs, l2k, k2r);
+    let p1 = create_a();
+
+    let mut overlap = Morphism::new();
+    overlap.insert_node(0.into(), la);
+    assert_eq!(p1.compose(&p2, overlap).unwrap_err(), RewriteError::DanglingCondition);
+    assert!(p1.dependency_overlaps(&p2).is_empty());
+
+    let mut bad = Morphism::new();
+    bad.insert_node(0.into(), lc);
+    assert_eq!(p1.compose(&p2, bad).unwrap_err(), RewriteError::InvalidOverlap);
+}
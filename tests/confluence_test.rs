@@ -0,0 +1,77 @@
+// tests/confluence_test.rs
+
+use pushout::{Rule, Morphism, Joinability, JoinLimits, check_local_confluence, is_isomorphic, find_isomorphism};
+use petgraph::graph::Graph;
+
+/// Relabel a `from` node to `to`, optionally growing a fresh X from it.
+fn relabel(from: &'static str, to: &'static str, grow: bool) -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    let l = lhs.add_node(from);
+    let mut interface = Graph::<&str, &str>::new();
+    let k = interface.add_node(from);
+    let mut rhs = Graph::<&str, &str>::new();
+    let r = rhs.add_node(to);
+    if grow {
+        let x = rhs.add_node("X");
+        rhs.add_edge(r, x, "grow");
+    }
+    let mut l2k = Morphism::new();
+    l2k.insert_node(l, k);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(k, r);
+    Rule::new(lhs, interface, rhs, l2k, k2r)
+}
+
+fn verdicts(rules: &[Rule<&'static str, &'static str>]) -> Vec<Joinability> {
+    check_local_confluence(rules, JoinLimits::default())
+        .unwrap()
+        .into_iter()
+        .map(|v| v.joinability)
+        .collect()
+}
+
+#[test]
+fn test_diverging_relabels_join_downstream() {
+    let rules = [
+        relabel("A", "C", false),
+        relabel("A", "D", false),
+        relabel("C", "E", false),
+        relabel("D", "E", false),
+    ];
+    assert_eq!(verdicts(&rules), [Joinability::StrictlyJoinable; 2]);
+}
+
+#[test]
+fn test_diverging_normal_forms_are_not_joinable() {
+    let rules = [relabel("A", "C", false), relabel("A", "D", false)];
+    assert_eq!(verdicts(&rules), [Joinability::NotJoinable; 2]);
+}
+
+#[test]
+fn test_unbounded_growth_hits_the_budget() {
+    let rules = [
+        relabel("A", "C", false),
+        relabel("A", "D", false),
+        relabel("C", "C", true),
+    ];
+    let found = verdicts(&rules);
+    assert_eq!(found.len(), 2);
+    assert!(found.iter().all(|&j| j == Joinability::BudgetExceeded));
+}
+
+#[test]
+fn test_isomorphism_check_stops_at_first_mapping() {
+    // 12 interchangeable nodes have 12! isomorphisms; one is enough
+    let mut a = Graph::<&str, &str>::new();
+    let mut b = Graph::<&str, &str>::new();
+    for _ in 0..12 {
+        a.add_node("A");
+        b.add_node("A");
+    }
+    assert!(is_isomorphic(&a, &b));
+    let f = find_isomorphism(&a, &b).unwrap();
+    assert_eq!(f.len(), 12);
+
+    b.add_node("A");
+    assert!(find_isomorphism(&a, &b).is_none());
+}
@@ -0,0 +1,81 @@
+// tests/critical_pair_test.rs
+
+use pushout::{Rule, Morphism, ConflictKind, critical_pairs, find_matches, apply_once};
+use petgraph::graph::Graph;
+
+/// A rule matching a single `from` node and preserving it as `to`,
+/// optionally attaching a fresh B.
+fn keep_a(to: &'static str, add_b: bool) -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    let la = lhs.add_node("A");
+    let mut interface = Graph::<&str, &str>::new();
+    let ka = interface.add_node("A");
+    let mut rhs = Graph::<&str, &str>::new();
+    let ra = rhs.add_node(to);
+    if add_b {
+        let rb = rhs.add_node("B");
+        rhs.add_edge(ra, rb, "ab");
+    }
+    let mut l2k = Morphism::new();
+    l2k.insert_node(la, ka);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(ka, ra);
+    Rule::new(lhs, interface, rhs, l2k, k2r)
+}
+
+fn delete_a() -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    lhs.add_node("A");
+    Rule::new(lhs, Graph::new(), Graph::new(), Morphism::new(), Morphism::new())
+}
+
+#[test]
+fn test_delete_use_conflict_is_asymmetric() {
+    let (del, extend) = (delete_a(), keep_a("A", true));
+
+    let pairs = critical_pairs(&del, &extend, false).unwrap();
+    assert_eq!(pairs.len(), 1);
+    assert_eq!(pairs[0].kind, ConflictKind::DeleteUse);
+    assert_eq!(pairs[0].graph.node_count(), 1, "overlap graph is minimal");
+
+    assert!(critical_pairs(&extend, &del, false).unwrap().is_empty());
+
+    // Only the trivial overlap of a rule with itself is skipped, however
+    // the rule is passed
+    assert!(critical_pairs(&del, &del, true).unwrap().is_empty());
+    assert!(critical_pairs(&del, &del.clone(), true).unwrap().is_empty());
+    assert_eq!(critical_pairs(&del, &delete_a(), false).unwrap().len(), 1);
+}
+
+#[test]
+fn test_relabelling_is_a_change_attribute_conflict() {
+    let relabel = keep_a("C", false);
+
+    let mut host = Graph::<&str, &str>::new();
+    host.add_node("A");
+    let m = &find_matches(&relabel, &host, true).unwrap()[0];
+    let result = apply_once(&relabel, &host, m).unwrap().graph;
+    assert_eq!(result.node_weights().copied().collect::<Vec<_>>(), ["C"]);
+
+    let pairs = critical_pairs(&relabel, &keep_a("A", true), false).unwrap();
+    assert_eq!(pairs.len(), 1);
+    assert_eq!(pairs[0].kind, ConflictKind::ChangeAttribute);
+}
+
+#[test]
+fn test_produce_forbid_conflict_through_nac() {
+    // "attach a B to an A that has none"
+    let extend = keep_a("A", true);
+    let mut forbidden = Graph::<&str, &str>::new();
+    let na = forbidden.add_node("A");
+    let nb = forbidden.add_node("B");
+    forbidden.add_edge(na, nb, "ab");
+    let mut l2n = Morphism::new();
+    l2n.insert_node(0.into(), na);
+    let guarded = keep_a("A", true).with_nac(forbidden, l2n);
+
+    let pairs = critical_pairs(&extend, &guarded, false).unwrap();
+    assert_eq!(pairs.len(), 1);
+    assert_eq!(pairs[0].kind, ConflictKind::ProduceForbid);
+    assert_eq!(pairs[0].graph.node_count(), 1);
+}
@@ -27,7 +27,7 @@ fn test_cypher_export_add_edge() {
     let rule = Rule::new(lhs, interface, rhs, l2k, k2r);
 
     // Export to Cypher
-    let cypher = rule.to_cypher();
+    let cypher = rule.to_cypher().expect("rule should be valid");
 
     // It should at least include:
     //  - a MATCH (or MERGE) for the preserved node,
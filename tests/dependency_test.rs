@@ -0,0 +1,98 @@
+// tests/dependency_test.rs
+
+use pushout::{
+    Rule, Morphism, DependencyKind, dependency_graph, dependency_dot, application_order, apply_rules,
+    apply_rules_in_order, RuleError,
+};
+use petgraph::graph::Graph;
+
+/// B => B -> C
+fn use_b() -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    let lb = lhs.add_node("B");
+    let mut interface = Graph::<&str, &str>::new();
+    let kb = interface.add_node("B");
+    let mut rhs = Graph::<&str, &str>::new();
+    let rb = rhs.add_node("B");
+    let rc = rhs.add_node("C");
+    rhs.add_edge(rb, rc, "bc");
+    let mut l2k = Morphism::new();
+    l2k.insert_node(lb, kb);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(kb, rb);
+    Rule::new(lhs, interface, rhs, l2k, k2r)
+}
+
+/// nothing => B
+fn make_b() -> Rule<&'static str, &'static str> {
+    let mut rhs = Graph::<&str, &str>::new();
+    rhs.add_node("B");
+    Rule::new(Graph::new(), Graph::new(), rhs, Morphism::new(), Morphism::new())
+}
+
+/// B => nothing
+fn drop_b() -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    lhs.add_node("B");
+    Rule::new(lhs, Graph::new(), Graph::new(), Morphism::new(), Morphism::new())
+}
+
+#[test]
+fn test_dependency_graph_edges_and_dot() {
+    let deps = dependency_graph(&[use_b(), make_b(), drop_b()]).unwrap();
+    let mut edges: Vec<_> = deps
+        .edge_indices()
+        .map(|e| {
+            let (src, dst) = deps.edge_endpoints(e).unwrap();
+            (deps[src], deps[dst], deps[e])
+        })
+        .collect();
+    edges.sort_by_key(|&(s, d, _)| (s, d));
+    assert_eq!(
+        edges,
+        [
+            (1, 0, DependencyKind::ProduceUse),
+            (1, 2, DependencyKind::ProduceUse),
+            (2, 0, DependencyKind::DeleteUse),
+        ]
+    );
+    assert!(dependency_dot(&deps).contains("produce-use"));
+}
+
+#[test]
+fn test_apply_rules_runs_producers_first() {
+    let host = Graph::<&str, &str>::new();
+    let result = apply_rules(&host, &[use_b(), make_b()]).unwrap();
+    assert_eq!(result.node_count(), 2, "B is created before it is extended");
+    assert_eq!(result.edge_count(), 1);
+
+    let rules = [use_b(), make_b()];
+    let order = application_order(&rules).unwrap();
+    assert_eq!(order, [1, 0]);
+    let again = apply_rules_in_order(&host, &rules, &order).unwrap();
+    assert_eq!(again.node_count(), 2);
+}
+
+#[test]
+fn test_application_order_rejects_cloning_rules() {
+    // B => B B, cloning the matched node
+    let mut lhs = Graph::<&str, &str>::new();
+    let lb = lhs.add_node("B");
+    let mut interface = Graph::<&str, &str>::new();
+    let k1 = interface.add_node("B");
+    let k2 = interface.add_node("B");
+    let rhs = interface.clone();
+    let mut k2l = Morphism::new();
+    k2l.insert_node(k1, lb);
+    k2l.insert_node(k2, lb);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(k1, k1);
+    k2r.insert_node(k2, k2);
+    let mut clone_b = Rule::new(lhs, interface, rhs, Morphism::new(), k2r);
+    clone_b.k2l = k2l;
+    assert!(clone_b.validate_sqpo().is_ok());
+    assert_eq!(
+        application_order(&[make_b(), clone_b]),
+        Err(RuleError::NotInjective { morphism: "k2l", node: lb })
+    );
+}
@@ -0,0 +1,58 @@
+// tests/derivation_test.rs
+
+use pushout::{Rule, Morphism, find_matches, apply_once};
+use petgraph::graph::Graph;
+
+/// Rule: delete a node "X" together with its edge from "A".
+fn delete_x() -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    let la = lhs.add_node("A");
+    let lx = lhs.add_node("X");
+    lhs.add_edge(la, lx, "ax");
+
+    let mut interface = Graph::<&str, &str>::new();
+    let ka = interface.add_node("A");
+
+    let mut rhs = Graph::<&str, &str>::new();
+    let ra = rhs.add_node("A");
+
+    let mut l2k = Morphism::new();
+    l2k.insert_node(la, ka);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(ka, ra);
+
+    Rule::new(lhs, interface, rhs, l2k, k2r)
+}
+
+#[test]
+fn test_tracking_follows_reshuffled_indices() {
+    // Host: X first so its removal moves the last node and edges around
+    let mut host = Graph::<&str, &str>::new();
+    let hx = host.add_node("X");
+    let hb = host.add_node("B");
+    let hc = host.add_node("C");
+    let ha = host.add_node("A");
+    host.add_edge(ha, hx, "ax");
+    host.add_edge(hb, hc, "bc");
+    host.add_edge(hc, ha, "ca");
+    host.add_edge(ha, hb, "ab");
+
+    let rule = delete_x();
+    let m = find_matches(&rule, &host, true).unwrap().remove(0);
+    let derivation = apply_once(&rule, &host, &m).unwrap();
+    let (graph, tracking) = (&derivation.graph, &derivation.tracking);
+
+    assert!(tracking.map_node(&hx).is_none(), "deleted node is untracked");
+    for n in [ha, hb, hc] {
+        let image = tracking.map_node(&n).expect("surviving node is tracked");
+        assert_eq!(graph[*image], host[n]);
+    }
+    assert_eq!(tracking.edge_map.len(), 3);
+    for (e, image) in &tracking.edge_map {
+        assert_eq!(graph[*image], host[*e]);
+    }
+
+    // The comatch puts the RHS node onto the tracked image of A
+    let ra = rule.rhs.node_indices().next().unwrap();
+    assert_eq!(derivation.comatch.map_node(&ra), tracking.map_node(&ha));
+}
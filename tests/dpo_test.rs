@@ -1,6 +1,7 @@
 // tests/dpo_tests.rs
 
-use pushout::{Rule, Morphism, find_matches, apply_once};
+use pushout::{Rule, Morphism, RewriteError, find_matches, apply_once};
+use pushout::transformation::apply_once_sqpo;
 use petgraph::graph::Graph;
 
 #[test]
@@ -23,11 +24,15 @@ fn test_delete_fails_on_dangling() {
     let k2r = Morphism::new();
     let rule = Rule::new(lhs, interface, rhs, l2k, k2r);
 
-    let matches = find_matches(&rule, &host, true);
+    let matches = find_matches(&rule, &host, true).unwrap();
     assert_eq!(matches.len(), 1, "Should find exactly one match for node B");
 
     let result = apply_once(&rule, &host, &matches[0]);
-    assert!(result.is_none(), "apply_once should return None due to dangling edges");
+    assert_eq!(
+        result.unwrap_err(),
+        RewriteError::DanglingCondition,
+        "apply_once should fail due to dangling edges"
+    );
 }
 
 #[test]
@@ -57,11 +62,12 @@ fn test_add_node_and_edge() {
 
     let rule = Rule::new(lhs, interface, rhs, l2k, k2r);
 
-    let matches = find_matches(&rule, &host, true);
+    let matches = find_matches(&rule, &host, true).unwrap();
     assert_eq!(matches.len(), 1, "Should find one match for the single node A");
 
     let result = apply_once(&rule, &host, &matches[0])
-        .expect("apply_once should succeed for an addition rule");
+        .expect("apply_once should succeed for an addition rule")
+        .graph;
 
     // After applying, we expect two nodes and one edge
     assert_eq!(result.node_count(), 2, "Result should have 2 nodes");
@@ -81,3 +87,50 @@ fn test_add_node_and_edge() {
         "Expected an edge A→B to have been created"
     );
 }
+
+#[test]
+fn test_malformed_rule_is_reported() {
+    use pushout::{RuleBuilder, RuleError};
+    use petgraph::graph::NodeIndex;
+
+    let mut lhs = Graph::<&str, &str>::new();
+    lhs.add_node("A");
+    let mut interface = Graph::<&str, &str>::new();
+    let ka = interface.add_node("A");
+
+    // k2l points at a node that does not exist in L
+    let mut k2l = Morphism::new();
+    k2l.insert_node(ka, NodeIndex::new(7));
+    let mut k2r = Morphism::new();
+    k2r.insert_node(ka, NodeIndex::new(0));
+
+    let built = RuleBuilder::new()
+        .lhs(lhs.clone())
+        .interface(interface.clone())
+        .rhs(interface.clone())
+        .k2l(k2l)
+        .k2r(k2r.clone())
+        .try_build();
+    assert!(matches!(built, Err(RuleError::DanglingNode { morphism: "k2l", .. })));
+
+    let missing = RuleBuilder::new().lhs(lhs).interface(interface).k2r(k2r).try_build();
+    assert!(matches!(missing, Err(RuleError::MissingComponent(_))));
+}
+
+#[test]
+fn test_incomplete_match_is_an_error() {
+    // A => nothing, applied with a match that maps no node
+    let mut lhs = Graph::<&str, &str>::new();
+    let la = lhs.add_node("A");
+    let rule = Rule::new(lhs, Graph::new(), Graph::new(), Morphism::new(), Morphism::new());
+    let mut host = Graph::<&str, &str>::new();
+    host.add_node("A");
+
+    let expected = RewriteError::IncompleteMatch { node: la };
+    assert_eq!(apply_once(&rule, &host, &Morphism::new()).unwrap_err(), expected);
+    assert_eq!(apply_once_sqpo(&rule, &host, &Morphism::new()).unwrap_err(), expected);
+
+    let mut outside = Morphism::new();
+    outside.insert_node(la, 5.into());
+    assert_eq!(apply_once(&rule, &host, &outside).unwrap_err(), expected);
+}
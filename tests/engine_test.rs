@@ -0,0 +1,49 @@
+// tests/engine_test.rs
+
+mod common;
+
+use pushout::{Morphism, RuleEngine, MatchOrder, Strategy};
+use common::{relabel, row, labels};
+
+#[test]
+fn test_priority_and_match_order() {
+    let mut host = row(3);
+    let mut engine = RuleEngine::new()
+        .rule(relabel("A", "B"), 0)
+        .rule(relabel("A", "C"), 1)
+        .match_order(MatchOrder::Key(Box::new(|_, m: &Morphism| -(m.node_map[&0.into()].index() as i64))));
+
+    assert_eq!(engine.step(&mut host).unwrap().map(|(i, _)| i), Some(1));
+    assert_eq!(labels(&host), ["A", "A", "C"], "highest host index first");
+
+    assert_eq!(engine.run(&mut host, 10).unwrap(), [1, 1]);
+    assert_eq!(labels(&host), ["C", "C", "C"]);
+}
+
+#[test]
+fn test_seeded_random_runs_are_reproducible() {
+    let run = |seed| {
+        let mut host = row(8);
+        let mut engine = RuleEngine::new()
+            .rule(relabel("A", "B"), 0)
+            .strategy(Strategy::Random { seed });
+        engine.run(&mut host, 4).unwrap();
+        labels(&host)
+    };
+    assert_eq!(run(7), run(7));
+    assert_eq!(run(7).iter().filter(|&&l| l == "B").count(), 4);
+    assert_ne!(run(7), run(8));
+}
+
+#[test]
+fn test_score_strategy_picks_the_best_candidate() {
+    let mut host = row(3);
+    let mut engine = RuleEngine::new()
+        .rule(relabel("A", "B"), 0)
+        .strategy(Strategy::Score(Box::new(|_, _, m: &Morphism| {
+            // Prefer the middle node
+            -(m.node_map[&0.into()].index() as i64 - 1).abs()
+        })));
+    engine.step(&mut host).unwrap();
+    assert_eq!(labels(&host), ["A", "B", "A"]);
+}
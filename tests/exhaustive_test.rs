@@ -0,0 +1,90 @@
+// tests/exhaustive_test.rs
+
+mod common;
+
+use pushout::{Rule, Morphism, apply_exhaustive, ExhaustiveLimits, Exhaustive, StopReason};
+use pushout::transformation::MergePolicy;
+use petgraph::graph::Graph;
+use common::{relabel, relabel_loop, row};
+
+/// Add a `B` node next to every `A` node.
+fn sprout() -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    let l = lhs.add_node("A");
+    let mut interface = Graph::<&str, &str>::new();
+    let k = interface.add_node("A");
+    let mut rhs = Graph::<&str, &str>::new();
+    let r = rhs.add_node("A");
+    let b = rhs.add_node("B");
+    rhs.add_edge(r, b, "next");
+    let mut l2k = Morphism::new();
+    l2k.insert_node(l, k);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(k, r);
+    Rule::new(lhs, interface, rhs, l2k, k2r)
+}
+
+#[test]
+fn test_normal_forms_are_deduplicated() {
+    let result = apply_exhaustive(&row(3), &relabel("A", "B"), ExhaustiveLimits::default()).unwrap();
+    let Exhaustive::NormalForms(graphs) = result else {
+        panic!("expected normal forms");
+    };
+    assert_eq!(graphs.len(), 1);
+    assert!(graphs[0].node_weights().all(|l| *l == "B"));
+}
+
+#[test]
+fn test_nonterminating_rules_stop() {
+    let rule = sprout();
+    assert!(rule.is_trivially_nonterminating());
+    assert!(!relabel("A", "B").is_trivially_nonterminating());
+    // A relabelled edge only matches again if the host label survives
+    assert!(!relabel_loop("e", "f").is_trivially_nonterminating());
+    assert!(relabel_loop("e", "f").with_merge_policy(MergePolicy::Survivor).is_trivially_nonterminating());
+    let result = apply_exhaustive(&row(1), &rule, ExhaustiveLimits::default()).unwrap();
+    assert!(matches!(result, Exhaustive::Stopped { reason: StopReason::NonTerminatingRule, .. }));
+
+    // A NAC hides the problem from the static check, so a limit stops it
+    let mut nac = Graph::new();
+    nac.add_node("A");
+    nac.add_node("Z");
+    let mut l2n = Morphism::new();
+    l2n.insert_node(0.into(), 0.into());
+    let guarded = sprout().with_nac(nac, l2n);
+    assert!(!guarded.is_trivially_nonterminating());
+    let limits = ExhaustiveLimits { max_branches: 5, ..Default::default() };
+    let result = apply_exhaustive(&row(1), &guarded, limits).unwrap();
+    assert!(matches!(result, Exhaustive::Stopped { reason: StopReason::MaxBranches, .. }));
+    let limits = ExhaustiveLimits { max_steps: 5, ..Default::default() };
+    let result = apply_exhaustive(&row(1), &guarded, limits).unwrap();
+    assert!(matches!(result, Exhaustive::Stopped { reason: StopReason::MaxSteps, .. }));
+}
+
+#[test]
+fn test_cycles_are_detected() {
+    // Reverse an edge between two A nodes, forever
+    let mut lhs = Graph::<&str, &str>::new();
+    let x = lhs.add_node("A");
+    let y = lhs.add_node("A");
+    lhs.add_edge(x, y, "e");
+    let mut interface = Graph::<&str, &str>::new();
+    let kx = interface.add_node("A");
+    let ky = interface.add_node("A");
+    let mut rhs = Graph::<&str, &str>::new();
+    let rx = rhs.add_node("A");
+    let ry = rhs.add_node("A");
+    rhs.add_edge(ry, rx, "e");
+    let mut l2k = Morphism::new();
+    l2k.insert_node(x, kx);
+    l2k.insert_node(y, ky);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(kx, rx);
+    k2r.insert_node(ky, ry);
+    let reverse = Rule::new(lhs.clone(), interface, rhs, l2k, k2r);
+
+    assert!(!reverse.is_trivially_nonterminating());
+    let result = apply_exhaustive(&lhs, &reverse, ExhaustiveLimits::default()).unwrap();
+    assert!(matches!(result, Exhaustive::Stopped { reason: StopReason::Cycle, .. }));
+    assert!(result.normal_forms().is_empty());
+}
@@ -0,0 +1,70 @@
+// tests/grammar_test.rs
+
+use pushout::{Rule, Morphism, Grammar};
+use petgraph::graph::Graph;
+
+/// Grow a path of `A` nodes at its `S` end, or stop by turning `S` into `A`.
+fn paths() -> Grammar<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    let l = lhs.add_node("S");
+    let mut interface = Graph::<&str, &str>::new();
+    let k = interface.add_node("S");
+    let mut rhs = Graph::<&str, &str>::new();
+    let a = rhs.add_node("A");
+    let s = rhs.add_node("S");
+    rhs.add_edge(a, s, "e");
+    let mut l2k = Morphism::new();
+    l2k.insert_node(l, k);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(k, a);
+    let extend = Rule::new(lhs.clone(), interface.clone(), rhs, l2k.clone(), k2r);
+
+    let mut rhs = Graph::<&str, &str>::new();
+    let a = rhs.add_node("A");
+    let mut k2r = Morphism::new();
+    k2r.insert_node(k, a);
+    let stop = Rule::new(lhs.clone(), interface, rhs, l2k, k2r);
+
+    Grammar::new(lhs).terminal("A").rule("extend", extend).rule("stop", stop)
+}
+
+fn path(n: usize) -> Graph<&'static str, &'static str> {
+    let mut g = Graph::new();
+    let nodes: Vec<_> = (0..n).map(|_| g.add_node("A")).collect();
+    for w in nodes.windows(2) {
+        g.add_edge(w[0], w[1], "e");
+    }
+    g
+}
+
+#[test]
+fn test_enumerate_and_membership() {
+    let grammar = paths();
+    assert!(grammar.rule_named("extend").is_some());
+
+    // Paths of 1, 2 and 3 nodes have size 1, 3 and 5
+    let members = grammar.enumerate(5).unwrap();
+    assert_eq!(members.len(), 3);
+    assert!(members.iter().all(|g| grammar.is_terminal(g)));
+
+    assert!(grammar.contains(&path(3), 5).unwrap());
+    assert!(!grammar.contains(&path(3), 4).unwrap());
+    let mut loop_graph = path(1);
+    loop_graph.add_edge(0.into(), 0.into(), "e");
+    assert!(!grammar.contains(&loop_graph, 5).unwrap());
+    let mut two = Graph::new();
+    two.add_node("A");
+    two.add_node("A");
+    assert!(!grammar.contains(&two, 5).unwrap());
+}
+
+#[test]
+fn test_random_members_are_reproducible() {
+    let grammar = paths();
+    for seed in 0..5 {
+        let member = grammar.random_member(seed, 9, 100).unwrap().unwrap();
+        assert!(grammar.contains(&member, 9).unwrap());
+        let again = grammar.random_member(seed, 9, 100).unwrap().unwrap();
+        assert_eq!(member.node_count(), again.node_count());
+    }
+}
@@ -0,0 +1,72 @@
+// tests/in_place_test.rs
+
+use pushout::{Rule, Morphism, RewriteError, GraphRewrite, find_matches, apply_once_mut};
+use petgraph::graph::Graph;
+
+/// Rule: delete a node "D" next to a node "A", and hang a new "N" off the "A".
+fn replace_d() -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    let ld = lhs.add_node("D");
+    let la = lhs.add_node("A");
+    lhs.add_edge(ld, la, "da");
+
+    let mut interface = Graph::<&str, &str>::new();
+    let ka = interface.add_node("A");
+
+    let mut rhs = Graph::<&str, &str>::new();
+    let ra = rhs.add_node("A");
+    let rn = rhs.add_node("N");
+    rhs.add_edge(ra, rn, "an");
+
+    let mut l2k = Morphism::new();
+    l2k.insert_node(la, ka);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(ka, ra);
+
+    Rule::new(lhs, interface, rhs, l2k, k2r)
+}
+
+#[test]
+fn test_in_place_rewrite_returns_comatch() {
+    // "A" is the last node, so deleting "D" moves it into index 0
+    let mut host = Graph::<&str, &str>::new();
+    let hd = host.add_node("D");
+    let hc = host.add_node("C");
+    let ha = host.add_node("A");
+    host.add_edge(hd, ha, "da");
+    host.add_edge(hc, ha, "ca");
+
+    let rule = replace_d();
+    let m = find_matches(&rule, &host, true).unwrap().remove(0);
+    let comatch = apply_once_mut(&mut host, &rule, &m).expect("rewrite should apply");
+
+    assert_eq!(host.node_count(), 3);
+    for r_node in rule.rhs.node_indices() {
+        let h_node = comatch.map_node(&r_node).unwrap();
+        assert_eq!(host[*h_node], rule.rhs[r_node], "comatch must preserve labels");
+    }
+    let r_edge = rule.rhs.edge_indices().next().unwrap();
+    let h_edge = *comatch.map_edge(&r_edge).unwrap();
+    assert_eq!(host[h_edge], "an");
+
+    // The untouched context edge C -> A still exists
+    let c = host.node_indices().find(|&n| host[n] == "C").unwrap();
+    let a = host.node_indices().find(|&n| host[n] == "A").unwrap();
+    assert!(host.find_edge(c, a).is_some());
+}
+
+#[test]
+fn test_apply_mut_leaves_host_untouched_on_failure() {
+    // D has a dangling edge to X, so DPO gluing fails
+    let mut host = Graph::<&str, &str>::new();
+    let hd = host.add_node("D");
+    let ha = host.add_node("A");
+    let hx = host.add_node("X");
+    host.add_edge(hd, ha, "da");
+    host.add_edge(hx, hd, "xd");
+
+    let rule = replace_d();
+    assert_eq!(rule.apply_mut(&mut host).unwrap_err(), RewriteError::DanglingCondition);
+    assert_eq!(host.node_count(), 3);
+    assert_eq!(host.edge_count(), 2);
+}
@@ -0,0 +1,78 @@
+// tests/independence_test.rs
+
+use pushout::{
+    Rule, Morphism, find_matches, apply_once, is_isomorphic,
+    are_parallel_independent, are_sequentially_independent, swap_steps,
+};
+use petgraph::graph::Graph;
+
+/// x => x -> C
+fn extend(x: &'static str) -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    let l = lhs.add_node(x);
+    let mut interface = Graph::<&str, &str>::new();
+    let k = interface.add_node(x);
+    let mut rhs = Graph::<&str, &str>::new();
+    let r = rhs.add_node(x);
+    let c = rhs.add_node("C");
+    rhs.add_edge(r, c, "to");
+    let mut l2k = Morphism::new();
+    l2k.insert_node(l, k);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(k, r);
+    Rule::new(lhs, interface, rhs, l2k, k2r)
+}
+
+/// x => nothing
+fn delete(x: &'static str) -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    lhs.add_node(x);
+    Rule::new(lhs, Graph::new(), Graph::new(), Morphism::new(), Morphism::new())
+}
+
+/// nothing => x
+fn create(x: &'static str) -> Rule<&'static str, &'static str> {
+    let mut rhs = Graph::<&str, &str>::new();
+    rhs.add_node(x);
+    Rule::new(Graph::new(), Graph::new(), rhs, Morphism::new(), Morphism::new())
+}
+
+fn first_match(rule: &Rule<&'static str, &'static str>, host: &Graph<&'static str, &'static str>) -> Morphism {
+    find_matches(rule, host, true).unwrap().remove(0)
+}
+
+#[test]
+fn test_parallel_independence() {
+    let mut host = Graph::<&str, &str>::new();
+    host.add_node("A");
+    host.add_node("B");
+
+    let (del_a, ext_b, ext_a) = (delete("A"), extend("B"), extend("A"));
+    let (ma, mb) = (first_match(&del_a, &host), first_match(&ext_b, &host));
+    assert!(are_parallel_independent(&del_a, &ma, &ext_b, &mb, &host).unwrap());
+
+    let ma2 = first_match(&ext_a, &host);
+    assert!(!are_parallel_independent(&del_a, &ma, &ext_a, &ma2, &host).unwrap());
+}
+
+#[test]
+fn test_sequential_steps_swap_to_isomorphic_result() {
+    let mut host = Graph::<&str, &str>::new();
+    host.add_node("A");
+
+    // Create B, then extend the old A: independent
+    let (make_b, ext_a) = (create("B"), extend("A"));
+    let m1 = first_match(&make_b, &host);
+    let h1 = apply_once(&make_b, &host, &m1).unwrap().graph;
+    let m2 = first_match(&ext_a, &h1);
+    let h2 = apply_once(&ext_a, &h1, &m2).unwrap().graph;
+
+    let (first, second) = swap_steps(&make_b, &m1, &ext_a, &m2, &host).unwrap().expect("independent");
+    assert_eq!(first.graph.node_count(), 2, "A was extended first");
+    assert!(is_isomorphic(&second.graph, &h2));
+
+    // Extending the B that was just created depends on the first step
+    let ext_b = extend("B");
+    let m2 = first_match(&ext_b, &h1);
+    assert!(!are_sequentially_independent(&make_b, &m1, &ext_b, &m2, &host).unwrap());
+}
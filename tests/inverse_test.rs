@@ -0,0 +1,80 @@
+// tests/inverse_test.rs
+
+use pushout::{Rule, Morphism, find_matches, apply_once, vf2_subgraph_isomorphism};
+use petgraph::graph::Graph;
+
+/// Same size and one embeds into the other: isomorphic.
+fn isomorphic(a: &Graph<&str, &str>, b: &Graph<&str, &str>) -> bool {
+    a.node_count() == b.node_count()
+        && a.edge_count() == b.edge_count()
+        && vf2_subgraph_isomorphism(a, b, true)
+}
+
+#[test]
+fn test_inverse_undoes_deletion_at_comatch() {
+    // Host: X <- A -> Y -> X
+    let mut host = Graph::<&str, &str>::new();
+    let ha = host.add_node("A");
+    let hx = host.add_node("X");
+    let hy = host.add_node("Y");
+    host.add_edge(ha, hx, "ax");
+    host.add_edge(ha, hy, "ay");
+    host.add_edge(hy, hx, "yx");
+
+    // Rule: delete Y together with the edge A -> Y, keeping A
+    let mut lhs = Graph::<&str, &str>::new();
+    let la = lhs.add_node("A");
+    let ly = lhs.add_node("Y");
+    let lx = lhs.add_node("X");
+    lhs.add_edge(la, ly, "ay");
+    lhs.add_edge(ly, lx, "yx");
+
+    let mut interface = Graph::<&str, &str>::new();
+    let ka = interface.add_node("A");
+    let kx = interface.add_node("X");
+    let mut rhs = Graph::<&str, &str>::new();
+    let ra = rhs.add_node("A");
+    let rx = rhs.add_node("X");
+
+    let mut l2k = Morphism::new();
+    l2k.insert_node(la, ka);
+    l2k.insert_node(lx, kx);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(ka, ra);
+    k2r.insert_node(kx, rx);
+    let rule = Rule::new(lhs, interface, rhs, l2k, k2r);
+
+    let m = &find_matches(&rule, &host, true).unwrap()[0];
+    let forward = apply_once(&rule, &host, m).unwrap();
+    assert_eq!(forward.graph.node_count(), 2);
+
+    let inverse = rule.inverse();
+    let backward = apply_once(&inverse, &forward.graph, &forward.comatch).unwrap();
+    assert!(isomorphic(&backward.graph, &host));
+}
+
+#[test]
+fn test_inverse_swaps_span() {
+    let mut lhs = Graph::<&str, &str>::new();
+    let la = lhs.add_node("A");
+    let mut interface = Graph::<&str, &str>::new();
+    let ka = interface.add_node("A");
+    let mut rhs = Graph::<&str, &str>::new();
+    let ra = rhs.add_node("A");
+    let rb = rhs.add_node("B");
+    rhs.add_edge(ra, rb, "ab");
+
+    let mut l2k = Morphism::new();
+    l2k.insert_node(la, ka);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(ka, ra);
+    let rule = Rule::new(lhs, interface, rhs, l2k, k2r);
+
+    let inverse = rule.inverse();
+    assert_eq!(inverse.lhs.node_count(), 2);
+    assert_eq!(inverse.rhs.node_count(), 1);
+    assert_eq!(inverse.k2l.map_node(&ka), Some(&ra));
+    assert_eq!(inverse.k2r.map_node(&ka), Some(&la));
+    assert!(inverse.validate().is_ok());
+    assert_eq!(inverse.inverse().lhs.node_count(), 1);
+}
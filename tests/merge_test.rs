@@ -0,0 +1,80 @@
+// tests/merge_test.rs
+
+use pushout::{Rule, Morphism, find_matches, apply_once};
+use pushout::transformation::MergePolicy;
+use petgraph::graph::Graph;
+
+/// Rule: fuse a node "P" and a node "Q" into a single node "PQ".
+fn merge_pq() -> Rule<String, &'static str> {
+    let mut lhs = Graph::<String, &str>::new();
+    let lp = lhs.add_node("P".to_string());
+    let lq = lhs.add_node("Q".to_string());
+
+    let mut interface = Graph::<String, &str>::new();
+    let kp = interface.add_node("P".to_string());
+    let kq = interface.add_node("Q".to_string());
+
+    let mut rhs = Graph::<String, &str>::new();
+    let rm = rhs.add_node("PQ".to_string());
+
+    let mut l2k = Morphism::new();
+    l2k.insert_node(lp, kp);
+    l2k.insert_node(lq, kq);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(kp, rm);
+    k2r.insert_node(kq, rm);
+
+    Rule::new(lhs, interface, rhs, l2k, k2r)
+}
+
+/// Host: X -a-> P -c-> Q <-b- Y
+fn host() -> Graph<String, &'static str> {
+    let mut host = Graph::<String, &str>::new();
+    let hx = host.add_node("X".to_string());
+    let hp = host.add_node("P".to_string());
+    let hq = host.add_node("Q".to_string());
+    let hy = host.add_node("Y".to_string());
+    host.add_edge(hx, hp, "a");
+    host.add_edge(hp, hq, "c");
+    host.add_edge(hy, hq, "b");
+    host
+}
+
+#[test]
+fn test_merge_redirects_all_edges() {
+    let host = host();
+    let rule = merge_pq();
+    assert!(rule.validate().is_ok());
+
+    let matches = find_matches(&rule, &host, true).unwrap();
+    assert_eq!(matches.len(), 1);
+    let derivation = apply_once(&rule, &host, &matches[0]).expect("merge should apply");
+    let result = &derivation.graph;
+
+    assert_eq!(result.node_count(), 3);
+    assert_eq!(result.edge_count(), 3);
+    let find = |w: &str| result.node_indices().find(|&n| result[n] == w).unwrap();
+    let (x, y, pq) = (find("X"), find("Y"), find("PQ"));
+    assert!(result.find_edge(x, pq).is_some());
+    assert!(result.find_edge(y, pq).is_some());
+    assert!(result.find_edge(pq, pq).is_some(), "P -c-> Q becomes a loop");
+
+    // Both merged host nodes are tracked onto the fused node
+    let (hp, hq) = (host.node_indices().nth(1).unwrap(), host.node_indices().nth(2).unwrap());
+    assert_eq!(derivation.tracking.map_node(&hp), Some(&pq));
+    assert_eq!(derivation.tracking.map_node(&hq), Some(&pq));
+}
+
+#[test]
+fn test_merge_policy_combines_weights() {
+    let host = host();
+    let rule = merge_pq().with_merge_policy(MergePolicy::Combine(|a, b| format!("{a}+{b}")));
+    let m = find_matches(&rule, &host, true).unwrap().remove(0);
+    let result = apply_once(&rule, &host, &m).unwrap().graph;
+    assert!(result.node_weights().any(|w| w == "P+Q"));
+
+    let rule = merge_pq().with_merge_policy(MergePolicy::Survivor);
+    let result = apply_once(&rule, &host, &m).unwrap().graph;
+    assert!(result.node_weights().any(|w| w == "P"));
+    assert!(!result.node_weights().any(|w| w == "Q"));
+}
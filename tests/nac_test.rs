@@ -0,0 +1,32 @@
+// tests/nac_test.rs
+
+use pushout::{Rule, Morphism, find_matches};
+use petgraph::graph::Graph;
+
+#[test]
+fn test_nac_filters_matches() {
+    // Host: A -> B, A
+    let mut host = Graph::<&str, &str>::new();
+    let a1 = host.add_node("A");
+    let b = host.add_node("B");
+    let a2 = host.add_node("A");
+    host.add_edge(a1, b, "ab");
+
+    // Match an A that has no outgoing edge to a B
+    let mut lhs = Graph::<&str, &str>::new();
+    let la = lhs.add_node("A");
+    let mut forbidden = Graph::<&str, &str>::new();
+    let na = forbidden.add_node("A");
+    let nb = forbidden.add_node("B");
+    forbidden.add_edge(na, nb, "ab");
+    let mut l2n = Morphism::new();
+    l2n.insert_node(la, na);
+
+    let rule = Rule::new(lhs, Graph::new(), Graph::new(), Morphism::new(), Morphism::new());
+    assert_eq!(find_matches(&rule, &host, true).unwrap().len(), 2);
+
+    let rule = rule.with_nac(forbidden, l2n);
+    let matches = find_matches(&rule, &host, true).unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].map_node(&la), Some(&a2));
+}
@@ -0,0 +1,135 @@
+// tests/parallel_test.rs
+
+use pushout::{Rule, Morphism, find_matches, apply_once, apply_parallel};
+use petgraph::graph::Graph;
+
+/// `from -e-> x  =>  from -e-> to`, or just `x => to` without `from`.
+fn relabel(from: Option<&'static str>, x: &'static str, to: &'static str) -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    let mut rhs = Graph::<&str, &str>::new();
+    let mut l2k = Morphism::new();
+    let mut k2r = Morphism::new();
+    if let Some(f) = from {
+        let (lf, rf) = (lhs.add_node(f), rhs.add_node(f));
+        l2k.insert_node(lf, lf);
+        k2r.insert_node(lf, rf);
+    }
+    let (lx, rx) = (lhs.add_node(x), rhs.add_node(to));
+    l2k.insert_node(lx, lx);
+    k2r.insert_node(lx, rx);
+    if from.is_some() {
+        let le = lhs.add_edge(0.into(), lx, "e");
+        let re = rhs.add_edge(0.into(), rx, "e");
+        l2k.insert_edge(le, le);
+        k2r.insert_edge(le, re);
+    }
+    let interface = lhs.clone();
+    Rule::new(lhs, interface, rhs, l2k, k2r)
+}
+
+fn labels(g: &Graph<&'static str, &'static str>) -> Vec<&'static str> {
+    let mut ls: Vec<_> = g.node_weights().copied().collect();
+    ls.sort_unstable();
+    ls
+}
+
+#[test]
+fn test_every_independent_match_fires_at_once() {
+    let mut host = Graph::<&str, &str>::new();
+    for _ in 0..3 {
+        host.add_node("A");
+    }
+    let result = apply_parallel(&host, &relabel(None, "A", "C")).unwrap();
+    assert_eq!(labels(&result), ["C", "C", "C"]);
+}
+
+#[test]
+fn test_conflicting_matches_fire_once() {
+    // Host: A1 -> B <- A2; both matches relabel the shared B
+    let mut host = Graph::<&str, &str>::new();
+    let a1 = host.add_node("A");
+    let b = host.add_node("B");
+    let a2 = host.add_node("A");
+    host.add_edge(a1, b, "e");
+    host.add_edge(a2, b, "e");
+
+    let rule = relabel(Some("A"), "B", "C");
+    assert_eq!(find_matches(&rule, &host, true).unwrap().len(), 2);
+    let result = apply_parallel(&host, &rule).unwrap();
+    assert_eq!(labels(&result), ["A", "A", "C"]);
+    assert_eq!(result.edge_count(), 2);
+}
+
+#[test]
+fn test_parallel_rule_is_disjoint_union() {
+    let (p1, p2) = (relabel(None, "A", "C"), relabel(Some("X"), "B", "D"));
+    let both = p1.parallel(&p2);
+    assert_eq!(both.lhs.node_count(), 3);
+    assert_eq!(both.lhs.edge_count(), 1);
+    assert!(both.validate().is_ok());
+
+    let mut host = Graph::<&str, &str>::new();
+    host.add_node("A");
+    let x = host.add_node("X");
+    let b = host.add_node("B");
+    host.add_edge(x, b, "e");
+    let m = &find_matches(&both, &host, true).unwrap()[0];
+    let result = apply_once(&both, &host, m).unwrap().graph;
+    assert_eq!(labels(&result), ["C", "D", "X"]);
+}
+
+#[test]
+fn test_matches_sharing_a_preserved_node_fire_together() {
+    // Host: a star X -> B with 20 leaves; every match keeps X unchanged
+    let mut host = Graph::<&str, &str>::new();
+    let x = host.add_node("X");
+    for _ in 0..20 {
+        let b = host.add_node("B");
+        host.add_edge(x, b, "e");
+    }
+    let result = apply_parallel(&host, &relabel(Some("X"), "B", "D")).unwrap();
+    assert_eq!(labels(&result).iter().filter(|l| **l == "D").count(), 20);
+    assert_eq!(result.edge_count(), 20);
+
+    let rule = relabel(Some("X"), "B", "D");
+    let folded = rule.parallel(&rule).parallel(&rule);
+    let copies = rule.parallel_copies(3);
+    assert_eq!(labels(&copies.lhs), labels(&folded.lhs));
+    assert_eq!(labels(&copies.rhs), labels(&folded.rhs));
+    assert_eq!(copies.k2l.node_map, folded.k2l.node_map);
+    assert_eq!(copies.k2l.edge_map, folded.k2l.edge_map);
+    assert_eq!(copies.k2r.edge_map, folded.k2r.edge_map);
+}
+
+#[test]
+fn test_matches_sharing_a_relabelled_edge_conflict() {
+    // A -e-> A  plus  C  =>  A -f-> A  plus  D: relabelling the loop disables
+    // the other match, so only one C becomes a D
+    let mut lhs = Graph::<&str, &str>::new();
+    let la = lhs.add_node("A");
+    let lc = lhs.add_node("C");
+    let le = lhs.add_edge(la, la, "e");
+    let mut rhs = Graph::<&str, &str>::new();
+    let ra = rhs.add_node("A");
+    let rc = rhs.add_node("D");
+    let re = rhs.add_edge(ra, ra, "f");
+    let mut l2k = Morphism::new();
+    let mut k2r = Morphism::new();
+    l2k.insert_node(la, la);
+    l2k.insert_node(lc, lc);
+    l2k.insert_edge(le, le);
+    k2r.insert_node(la, ra);
+    k2r.insert_node(lc, rc);
+    k2r.insert_edge(le, re);
+    let rule = Rule::new(lhs.clone(), lhs, rhs, l2k, k2r);
+
+    let mut host = Graph::<&str, &str>::new();
+    let a = host.add_node("A");
+    host.add_edge(a, a, "e");
+    host.add_node("C");
+    host.add_node("C");
+    assert_eq!(find_matches(&rule, &host, true).unwrap().len(), 2);
+    let result = apply_parallel(&host, &rule).unwrap();
+    assert_eq!(labels(&result), ["A", "C", "D"]);
+    assert_eq!(result.edge_weights().copied().collect::<Vec<_>>(), ["f"]);
+}
@@ -0,0 +1,50 @@
+// tests/program_test.rs
+
+mod common;
+
+use pushout::Program;
+use common::{relabel, row, nodes, labels};
+
+#[test]
+fn test_loop_runs_until_no_rule_applies() {
+    let result = Program::rule(relabel("A", "B")).alap().run(&row(4)).unwrap().unwrap();
+    assert_eq!(labels(&result), ["B"; 4]);
+}
+
+#[test]
+fn test_sequence_backtracks_into_rule_choice() {
+    // {A => B, A => C}; C => D  only succeeds via the second choice
+    let program = Program::Seq(vec![
+        Program::Rules(vec![relabel("A", "B"), relabel("A", "C")]),
+        Program::rule(relabel("C", "D")),
+    ]);
+    assert_eq!(labels(&program.run(&nodes(&["A"])).unwrap().unwrap()), ["D"]);
+
+    let stuck = Program::Seq(vec![Program::rule(relabel("A", "B")), Program::rule(relabel("C", "D"))]);
+    assert!(stuck.run(&nodes(&["A"])).unwrap().is_none());
+}
+
+#[test]
+fn test_if_discards_and_try_keeps_the_condition_result() {
+    let cond = || Program::rule(relabel("A", "B"));
+
+    let iffy = Program::if_then_else(cond(), Program::rule(relabel("A", "C")), Program::Fail);
+    assert_eq!(labels(&iffy.run(&nodes(&["A"])).unwrap().unwrap()), ["C"]);
+
+    let tried = Program::try_then_else(cond(), Program::rule(relabel("B", "C")), Program::Fail);
+    assert_eq!(labels(&tried.run(&nodes(&["A"])).unwrap().unwrap()), ["C"]);
+
+    // The else branch only runs when the condition fails
+    let fallback = Program::try_then_else(cond(), Program::Fail, Program::Skip);
+    assert_eq!(labels(&fallback.run(&nodes(&["X"])).unwrap().unwrap()), ["X"]);
+    assert!(fallback.run(&nodes(&["A"])).unwrap().is_none());
+}
+
+#[test]
+fn test_or_falls_back_when_the_rest_fails() {
+    let program = Program::Seq(vec![
+        Program::rule(relabel("A", "B")).or(Program::rule(relabel("A", "C"))),
+        Program::rule(relabel("C", "D")),
+    ]);
+    assert_eq!(labels(&program.run(&nodes(&["A"])).unwrap().unwrap()), ["D"]);
+}
@@ -0,0 +1,72 @@
+// tests/relabel_test.rs
+
+use pushout::{Rule, Morphism, find_matches, apply_once};
+use pushout::transformation::MergePolicy;
+use petgraph::graph::Graph;
+
+/// Rule: keep a node "A" and give it the RHS label "B".
+fn a_to_b() -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    let l = lhs.add_node("A");
+    let mut interface = Graph::<&str, &str>::new();
+    let k = interface.add_node("A");
+    let mut rhs = Graph::<&str, &str>::new();
+    let r = rhs.add_node("B");
+    let mut l2k = Morphism::new();
+    l2k.insert_node(l, k);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(k, r);
+    Rule::new(lhs, interface, rhs, l2k, k2r)
+}
+
+#[test]
+fn test_preserved_node_relabelling() {
+    // Host: A -e-> C
+    let mut host = Graph::new();
+    let a = host.add_node("A");
+    let c = host.add_node("C");
+    host.add_edge(a, c, "e");
+
+    // Default policy: the preserved node takes its RHS label, edges stay
+    let rule = a_to_b();
+    let m = &find_matches(&rule, &host, true).unwrap()[0];
+    let d = apply_once(&rule, &host, m).unwrap();
+    let kept = d.tracking.node_map[&a];
+    assert_eq!(d.graph[kept], "B");
+    assert_eq!(d.graph.node_count(), 2);
+    assert_eq!(d.graph.edge_count(), 1);
+
+    // Survivor policy: the host label is kept
+    let rule = a_to_b().with_merge_policy(MergePolicy::Survivor);
+    let d = apply_once(&rule, &host, m).unwrap();
+    assert_eq!(d.graph[d.tracking.node_map[&a]], "A");
+}
+
+#[test]
+fn test_preserved_edge_relabelling() {
+    // Rule: keep A -e-> A and give the edge the RHS label "f"
+    let mut lhs = Graph::<&str, &str>::new();
+    let (x, y) = (lhs.add_node("A"), lhs.add_node("A"));
+    let le = lhs.add_edge(x, y, "e");
+    let interface = lhs.clone();
+    let mut rhs = Graph::<&str, &str>::new();
+    let (rx, ry) = (rhs.add_node("A"), rhs.add_node("A"));
+    let re = rhs.add_edge(rx, ry, "f");
+    let mut l2k = Morphism::new();
+    l2k.insert_node(x, x);
+    l2k.insert_node(y, y);
+    l2k.insert_edge(le, le);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(x, rx);
+    k2r.insert_node(y, ry);
+    k2r.insert_edge(le, re);
+    let rule = Rule::new(lhs.clone(), interface, rhs, l2k, k2r);
+
+    // Nodes and edges follow the same rule under each policy
+    let m = &find_matches(&rule, &lhs, true).unwrap()[0];
+    let d = apply_once(&rule, &lhs, m).unwrap();
+    assert_eq!(d.graph.edge_weights().collect::<Vec<_>>(), [&"f"]);
+    let rule = rule.with_merge_policy(MergePolicy::Survivor);
+    let d = apply_once(&rule, &lhs, m).unwrap();
+    assert_eq!(d.graph.edge_weights().collect::<Vec<_>>(), [&"e"]);
+}
@@ -0,0 +1,76 @@
+// tests/schema_test.rs
+
+use pushout::transformation::{Rule, Morphism, RuleSchema, Label, Constraint};
+use petgraph::graph::Graph;
+
+/// Schema "x  =>  x -copy-> x" for any node label x.
+fn copy_schema() -> RuleSchema<&'static str, &'static str> {
+    let mut lhs = Graph::<Label<&str>, Label<&str>>::new();
+    let lx = lhs.add_node(Label::Var("x".into()));
+
+    let mut interface = Graph::<Label<&str>, Label<&str>>::new();
+    let kx = interface.add_node(Label::Var("x".into()));
+
+    let mut rhs = Graph::<Label<&str>, Label<&str>>::new();
+    let rx = rhs.add_node(Label::Var("x".into()));
+    let rc = rhs.add_node(Label::Var("x".into()));
+    rhs.add_edge(rx, rc, Label::Const("copy"));
+
+    let mut l2k = Morphism::new();
+    l2k.insert_node(lx, kx);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(kx, rx);
+
+    RuleSchema::new(Rule::new(lhs, interface, rhs, l2k, k2r))
+}
+
+#[test]
+fn test_variable_is_substituted_into_new_nodes() {
+    let mut host = Graph::<&str, &str>::new();
+    host.add_node("A");
+    host.add_node("B");
+
+    let schema = copy_schema();
+    let matches = schema.find_matches(&host, true).unwrap();
+    assert_eq!(matches.len(), 2, "x should match every node");
+
+    for (m, binding) in &matches {
+        let result = schema.apply_once(&host, m, binding).expect("copy should apply").graph;
+        let bound = binding.nodes["x"];
+        assert_eq!(result.node_count(), 3);
+        assert_eq!(result.node_weights().filter(|&&w| w == bound).count(), 2);
+        assert_eq!(result.edge_count(), 1);
+    }
+}
+
+#[test]
+fn test_variable_binds_consistently_and_respects_constraints() {
+    // Host: A -> A, A -> B
+    let mut host = Graph::<&str, &str>::new();
+    let a1 = host.add_node("A");
+    let a2 = host.add_node("A");
+    let b = host.add_node("B");
+    host.add_edge(a1, a2, "e");
+    host.add_edge(a1, b, "e");
+
+    // Pattern: x -> x (both ends must carry the same label)
+    let mut lhs = Graph::<Label<&str>, Label<&str>>::new();
+    let l0 = lhs.add_node(Label::Var("x".into()));
+    let l1 = lhs.add_node(Label::Var("x".into()));
+    lhs.add_edge(l0, l1, Label::Var("y".into()));
+    let schema = RuleSchema::new(Rule::new(
+        lhs,
+        Graph::new(),
+        Graph::new(),
+        Morphism::new(),
+        Morphism::new(),
+    ));
+
+    let matches = schema.find_matches(&host, true).unwrap();
+    assert_eq!(matches.len(), 1, "only A -> A binds x consistently");
+    assert_eq!(matches[0].1.nodes["x"], "A");
+    assert_eq!(matches[0].1.edges["y"], "e");
+
+    let restricted = schema.constrain_node("x", Constraint::OneOf(vec!["B"]));
+    assert!(restricted.find_matches(&host, true).unwrap().is_empty());
+}
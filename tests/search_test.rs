@@ -0,0 +1,74 @@
+// tests/search_test.rs
+
+mod common;
+
+use pushout::{Rule, Morphism, Planner, Goal, apply_once, is_isomorphic};
+use petgraph::graph::Graph;
+use common::{relabel, row, nodes};
+
+/// Add a `label` node.
+fn grow(label: &'static str) -> Rule<&'static str, &'static str> {
+    let mut rhs = Graph::new();
+    rhs.add_node(label);
+    Rule::new(Graph::new(), Graph::new(), rhs, Morphism::new(), Morphism::new())
+}
+
+#[test]
+fn test_cheapest_plan_respects_rule_costs() {
+    let goal = Goal::Pattern(nodes(&["C"]));
+    let planner = Planner::new()
+        .rule(relabel("A", "B"), 1.0)
+        .rule(relabel("B", "C"), 1.0)
+        .rule(relabel("A", "C"), 5.0);
+    let plan = planner.search(&nodes(&["A"]), &goal).unwrap().unwrap();
+    let rules: Vec<usize> = plan.steps.iter().map(|(r, _)| *r).collect();
+    assert_eq!(rules, vec![0, 1]);
+    assert_eq!(plan.cost, 2.0);
+
+    let planner = Planner::new()
+        .rule(relabel("A", "B"), 1.0)
+        .rule(relabel("B", "C"), 1.0)
+        .rule(relabel("A", "C"), 1.5);
+    let plan = planner.search(&nodes(&["A"]), &goal).unwrap().unwrap();
+    assert_eq!(plan.steps.len(), 1);
+    assert_eq!(plan.steps[0].0, 2);
+}
+
+#[test]
+fn test_plan_replays_to_target_graph() {
+    let target = nodes(&["B", "B"]);
+    let planner = Planner::new()
+        .rule(grow("A"), 1.0)
+        .rule(relabel("A", "B"), 1.0)
+        .heuristic(Box::new(|g: &Graph<&str, &str>| {
+            let b = g.node_weights().filter(|l| **l == "B").count();
+            2usize.saturating_sub(b) as f64
+        }));
+    let plan = planner.search(&nodes(&["A"]), &Goal::Graph(target.clone())).unwrap().unwrap();
+    assert_eq!(plan.cost, 3.0);
+
+    // The steps form a derivation from the start graph
+    let mut graph = nodes(&["A"]);
+    for (i, m) in &plan.steps {
+        let rule = if *i == 0 { grow("A") } else { relabel("A", "B") };
+        graph = apply_once(&rule, &graph, m).unwrap().graph;
+    }
+    assert!(is_isomorphic(&graph, &target));
+    assert!(is_isomorphic(&plan.graph, &target));
+}
+
+#[test]
+fn test_unreachable_goal_within_limit() {
+    let planner = Planner::new().rule(grow("A"), 1.0).max_states(20);
+    assert!(planner.search(&nodes(&[]), &Goal::Pattern(nodes(&["C"]))).unwrap().is_none());
+}
+
+#[test]
+fn test_symmetric_host_replays_quickly() {
+    // Every intermediate graph has up to 10! automorphisms
+    let planner = Planner::new().rule(relabel("A", "B"), 1.0);
+    let target = nodes(&["B"; 10]);
+    let plan = planner.search(&row(10), &Goal::Graph(target.clone())).unwrap().unwrap();
+    assert_eq!(plan.steps.len(), 10);
+    assert!(is_isomorphic(&plan.graph, &target));
+}
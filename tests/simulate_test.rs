@@ -0,0 +1,69 @@
+// tests/simulate_test.rs
+
+mod common;
+
+use pushout::{Rule, Morphism, random_walk, gillespie, Pick, StochasticRule};
+use petgraph::graph::Graph;
+use common::{relabel, row, labels};
+
+/// Delete a `label` node.
+fn decay(label: &'static str) -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    lhs.add_node(label);
+    Rule::new(lhs, Graph::new(), Graph::new(), Morphism::new(), Morphism::new())
+}
+
+#[test]
+fn test_random_walk_is_reproducible() {
+    let host = row(6);
+    let rules = vec![relabel("A", "B"), relabel("A", "C")];
+    for pick in [Pick::UniformMatch, Pick::UniformRule] {
+        let first = random_walk(&host, &rules, pick, 7, 10).unwrap();
+        let second = random_walk(&host, &rules, pick, 7, 10).unwrap();
+
+        // Every A gets relabelled once, then nothing applies
+        assert_eq!(first.events.len(), 6);
+        assert!(labels(&first.graph).iter().all(|l| *l != "A"));
+        assert_eq!(labels(&first.graph), labels(&second.graph));
+        let rules_of = |t: &pushout::Trace<_, _>| t.events.iter().map(|e| e.rule).collect::<Vec<_>>();
+        assert_eq!(rules_of(&first), rules_of(&second));
+    }
+
+    let short = random_walk(&host, &rules, Pick::UniformMatch, 7, 2).unwrap();
+    assert_eq!(short.events.len(), 2);
+    assert_eq!(short.events[1].time, 2.0);
+}
+
+#[test]
+fn test_gillespie_advances_time_until_exhausted() {
+    let host = row(5);
+    let rules = vec![StochasticRule::new(decay("A"), 1.0)];
+    let trace = gillespie(&host, &rules, 42, f64::INFINITY, 100).unwrap();
+    assert_eq!(trace.events.len(), 5);
+    assert_eq!(trace.graph.node_count(), 0);
+    assert!(trace.events.windows(2).all(|w| w[0].time < w[1].time));
+    assert!(trace.events[0].time > 0.0);
+
+    let again = gillespie(&host, &rules, 42, f64::INFINITY, 100).unwrap();
+    let times = |t: &pushout::Trace<_, _>| t.events.iter().map(|e| e.time).collect::<Vec<_>>();
+    assert_eq!(times(&trace), times(&again));
+
+    // Stopping at a time bound keeps only the earlier events
+    let cut = trace.events[2].time;
+    let bounded = gillespie(&host, &rules, 42, cut, 100).unwrap();
+    assert_eq!(bounded.events.len(), 3);
+    assert_eq!(bounded.graph.node_count(), 2);
+}
+
+#[test]
+fn test_gillespie_samples_by_propensity() {
+    let host = row(4);
+    let rules = vec![
+        StochasticRule::new(relabel("A", "B"), 0.0),
+        StochasticRule::new(relabel("A", "C"), 2.0),
+    ];
+    let trace = gillespie(&host, &rules, 3, f64::INFINITY, 100).unwrap();
+    assert_eq!(trace.events.len(), 4);
+    assert!(trace.events.iter().all(|e| e.rule == 1));
+    assert_eq!(labels(&trace.graph), vec!["C"; 4]);
+}
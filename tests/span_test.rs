@@ -0,0 +1,73 @@
+// tests/span_test.rs
+
+use pushout::{Rule, Morphism, RuleError, find_matches, apply_once};
+use petgraph::graph::Graph;
+
+#[test]
+fn test_span_rule_preserves_edge_and_adds_node() {
+    // Host: A -> B
+    let mut host = Graph::<&str, &str>::new();
+    let ha = host.add_node("A");
+    let hb = host.add_node("B");
+    host.add_edge(ha, hb, "ab");
+
+    // Span: (A -ab-> B) <- (A -ab-> B) -> (A -ab-> B -bc-> C)
+    let mut lhs = Graph::<&str, &str>::new();
+    let la = lhs.add_node("A");
+    let lb = lhs.add_node("B");
+    let le = lhs.add_edge(la, lb, "ab");
+
+    let interface = lhs.clone();
+    let (ka, kb, ke) = (la, lb, le);
+
+    let mut rhs = lhs.clone();
+    let rc = rhs.add_node("C");
+    rhs.add_edge(lb, rc, "bc");
+
+    let mut k2l = Morphism::new();
+    k2l.insert_node(ka, la);
+    k2l.insert_node(kb, lb);
+    k2l.insert_edge(ke, le);
+    let k2r = k2l.clone();
+
+    let rule = Rule::from_span(lhs, interface, rhs, k2l, k2r).expect("span should be valid");
+    let matches = find_matches(&rule, &host, true).unwrap();
+    assert_eq!(matches.len(), 1);
+
+    let derivation = apply_once(&rule, &host, &matches[0]).unwrap();
+    assert_eq!(derivation.graph.node_count(), 3);
+    assert_eq!(derivation.graph.edge_count(), 2);
+    let kept = *derivation.comatch.map_edge(&le).expect("preserved edge in comatch");
+    assert_eq!(derivation.graph[kept], "ab");
+}
+
+#[test]
+fn test_span_rejects_partial_and_non_injective_morphisms() {
+    let mut lhs = Graph::<&str, &str>::new();
+    let la = lhs.add_node("A");
+    let lb = lhs.add_node("B");
+    lhs.add_edge(la, lb, "ab");
+
+    let mut interface = Graph::<&str, &str>::new();
+    let ka = interface.add_node("A");
+    let kb = interface.add_node("B");
+    let ke = interface.add_edge(ka, kb, "ab");
+
+    let mut rhs = Graph::<&str, &str>::new();
+    let r0 = rhs.add_node("A");
+
+    // k2l leaves the interface edge unmapped
+    let mut k2l = Morphism::new();
+    k2l.insert_node(ka, la);
+    k2l.insert_node(kb, lb);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(ka, r0);
+    k2r.insert_node(kb, r0);
+    let err = Rule::from_span(lhs.clone(), interface.clone(), rhs.clone(), k2l.clone(), k2r.clone());
+    assert_eq!(err.err(), Some(RuleError::NotTotalEdge { morphism: "k2l", edge: ke }));
+
+    // Once k2l is total, k2r is rejected for merging both nodes
+    interface.clear_edges();
+    let err = Rule::from_span(lhs, interface, rhs, k2l, k2r);
+    assert_eq!(err.err(), Some(RuleError::NotInjective { morphism: "k2r", node: r0 }));
+}
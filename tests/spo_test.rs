@@ -0,0 +1,54 @@
+// tests/spo_test.rs
+
+use pushout::{Rule, Morphism, RewriteError, find_matches, RewriteMode, apply_rule, apply_rule_with};
+use pushout::transformation::spo;
+use petgraph::graph::Graph;
+
+/// Rule: delete a node "B" (no interface, empty RHS).
+fn delete_b() -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    lhs.add_node("B");
+    Rule::new(lhs, Graph::new(), Graph::new(), Morphism::new(), Morphism::new())
+}
+
+#[test]
+fn test_spo_deletes_dangling_edges() {
+    // Host: A -> B -> C, C -> A
+    let mut host = Graph::<&str, &str>::new();
+    let ha = host.add_node("A");
+    let hb = host.add_node("B");
+    let hc = host.add_node("C");
+    host.add_edge(ha, hb, "ab");
+    host.add_edge(hb, hc, "bc");
+    host.add_edge(hc, ha, "ca");
+
+    let rule = delete_b();
+    let matches = find_matches(&rule, &host, true).unwrap();
+    assert_eq!(matches.len(), 1);
+
+    let result = spo::apply_once(&rule, &host, &matches[0]).unwrap().graph;
+    assert_eq!(result.node_count(), 2);
+    assert_eq!(result.edge_count(), 1, "only C -> A should survive");
+    let edge = result.edge_indices().next().unwrap();
+    let (src, dst) = result.edge_endpoints(edge).unwrap();
+    assert_eq!((result[src], result[dst]), ("C", "A"));
+}
+
+#[test]
+fn test_rewrite_mode_selects_semantics() {
+    let mut host = Graph::<&str, &str>::new();
+    let ha = host.add_node("A");
+    let hb = host.add_node("B");
+    host.add_edge(ha, hb, "ab");
+
+    let rule = delete_b();
+    assert_eq!(
+        apply_rule(&host, &rule).unwrap_err(),
+        RewriteError::DanglingCondition,
+        "DPO must refuse dangling deletion"
+    );
+
+    let result = apply_rule_with(&host, &rule, RewriteMode::Spo).expect("SPO always applies");
+    assert_eq!(result.node_count(), 1);
+    assert_eq!(result[result.node_indices().next().unwrap()], "A");
+}
@@ -0,0 +1,78 @@
+// tests/sqpo_test.rs
+
+use pushout::{Rule, Morphism, RuleBuilder, RuleError, RewriteError, find_matches, apply_once};
+use pushout::transformation::apply_once_sqpo;
+use petgraph::graph::Graph;
+
+/// Rule: clone a node "P" and link the original to its copy.
+fn clone_p() -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    let lp = lhs.add_node("P");
+
+    let mut interface = Graph::<&str, &str>::new();
+    let k1 = interface.add_node("P");
+    let k2 = interface.add_node("P");
+
+    let mut rhs = Graph::<&str, &str>::new();
+    let r1 = rhs.add_node("P");
+    let r2 = rhs.add_node("P");
+    rhs.add_edge(r1, r2, "copy");
+
+    let mut k2l = Morphism::new();
+    k2l.insert_node(k1, lp);
+    k2l.insert_node(k2, lp);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(k1, r1);
+    k2r.insert_node(k2, r2);
+
+    RuleBuilder::new()
+        .lhs(lhs)
+        .interface(interface)
+        .rhs(rhs)
+        .k2l(k2l)
+        .k2r(k2r)
+        .build()
+}
+
+#[test]
+fn test_clone_copies_context_edges() {
+    // Host: A -knows-> P -owns-> C
+    let mut host = Graph::<&str, &str>::new();
+    let ha = host.add_node("A");
+    let hp = host.add_node("P");
+    let hc = host.add_node("C");
+    host.add_edge(ha, hp, "knows");
+    host.add_edge(hp, hc, "owns");
+
+    let rule = clone_p();
+    assert!(rule.validate().is_err(), "cloning rules are not DPO rules");
+    assert!(rule.validate_sqpo().is_ok());
+
+    let matches = find_matches(&rule, &host, true).unwrap();
+    assert_eq!(matches.len(), 1);
+    let result = apply_once_sqpo(&rule, &host, &matches[0]).unwrap().graph;
+
+    assert_eq!(result.node_count(), 4);
+    assert_eq!(result.edge_count(), 5);
+    let ps: Vec<_> = result.node_indices().filter(|&n| result[n] == "P").collect();
+    let a = result.node_indices().find(|&n| result[n] == "A").unwrap();
+    let c = result.node_indices().find(|&n| result[n] == "C").unwrap();
+    assert_eq!(ps.len(), 2);
+    for &p in &ps {
+        assert!(result.find_edge(a, p).is_some(), "every copy is known by A");
+        assert!(result.find_edge(p, c).is_some(), "every copy owns C");
+    }
+    assert!(result.find_edge(ps[0], ps[1]).is_some() || result.find_edge(ps[1], ps[0]).is_some());
+}
+
+#[test]
+fn test_dpo_rejects_cloning_rule() {
+    let mut host = Graph::<&str, &str>::new();
+    host.add_node("P");
+    let rule = clone_p();
+    let m = find_matches(&rule, &host, true).unwrap().remove(0);
+    assert!(matches!(
+        apply_once(&rule, &host, &m),
+        Err(RewriteError::InvalidRule(RuleError::NotInjective { morphism: "k2l", .. }))
+    ));
+}
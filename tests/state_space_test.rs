@@ -0,0 +1,84 @@
+// tests/state_space_test.rs
+
+mod common;
+
+use pushout::{Rule, Morphism, explore, ExploreOptions, SearchOrder};
+use petgraph::graph::Graph;
+use common::{relabel, row};
+
+#[test]
+fn test_states_are_deduplicated_up_to_isomorphism() {
+    let host = row(3);
+    let rules = vec![relabel("A", "B")];
+    for order in [SearchOrder::Bfs, SearchOrder::Dfs] {
+        let space = explore(&host, &rules, ExploreOptions { order, ..Default::default() }).unwrap();
+        // One state per number of B nodes, one transition per match
+        assert_eq!(space.lts.node_count(), 4);
+        assert_eq!(space.lts.edge_count(), 3 + 2 + 1);
+        assert!(space.is_complete());
+        let terminal = space.terminal_states();
+        assert_eq!(terminal.len(), 1);
+        assert!(space.lts[terminal[0]].node_weights().all(|l| *l == "B"));
+    }
+}
+
+#[test]
+fn test_symmetric_states_are_deduplicated_quickly() {
+    // 2^10 labellings collapse to 11 states; each check stops at one of the
+    // up to 10! isomorphisms
+    let options = ExploreOptions { max_depth: 11, ..Default::default() };
+    let space = explore(&row(10), &[relabel("A", "B")], options).unwrap();
+    assert_eq!(space.lts.node_count(), 11);
+    assert_eq!(space.lts.edge_count(), (1..=10).sum::<usize>());
+    assert!(space.is_complete());
+}
+
+#[test]
+fn test_cyclic_rules_terminate() {
+    let host = row(1);
+    let rules = vec![relabel("A", "B"), relabel("B", "A")];
+    let space = explore(&host, &rules, ExploreOptions::default()).unwrap();
+    assert_eq!(space.lts.node_count(), 2);
+    assert_eq!(space.lts.edge_count(), 2);
+    assert!(space.is_complete());
+    assert!(space.terminal_states().is_empty());
+    let back = space.lts.edge_indices().find(|e| space.lts[*e].rule == 1).unwrap();
+    assert_eq!(space.lts.edge_endpoints(back).unwrap().1, space.initial);
+}
+
+#[test]
+fn test_limits_cut_off_infinite_spaces() {
+    // Keep adding A nodes
+    let mut rhs = Graph::new();
+    rhs.add_node("A");
+    let grow = Rule::new(Graph::new(), Graph::new(), rhs, Morphism::new(), Morphism::new());
+    let rules = vec![grow];
+
+    let options = ExploreOptions { max_depth: 3, ..Default::default() };
+    let space = explore(&row(0), &rules, options).unwrap();
+    assert_eq!(space.lts.node_count(), 4);
+    assert_eq!(space.frontier.len(), 1);
+    assert_eq!(space.lts[space.frontier[0]].node_count(), 3);
+    assert!(space.terminal_states().is_empty());
+
+    let options = ExploreOptions { max_states: 2, ..Default::default() };
+    let space = explore(&row(0), &rules, options).unwrap();
+    assert_eq!(space.lts.node_count(), 2);
+    assert!(!space.is_complete());
+}
+
+#[test]
+fn test_dead_end_at_max_depth_is_terminal() {
+    // Delete an A; after one step no rule applies
+    let mut lhs = Graph::new();
+    lhs.add_node("A");
+    let delete = Rule::new(lhs, Graph::new(), Graph::new(), Morphism::new(), Morphism::new());
+
+    let options = ExploreOptions { max_depth: 1, ..Default::default() };
+    let space = explore(&row(1), &[delete], options).unwrap();
+    assert_eq!(space.lts.node_count(), 2);
+    assert!(space.is_complete());
+    let terminal = space.terminal_states();
+    assert_eq!(terminal.len(), 1);
+    assert_eq!(space.lts[terminal[0]].node_count(), 0);
+}
@@ -0,0 +1,50 @@
+// tests/temporal_test.rs
+
+mod common;
+
+use pushout::{Morphism, explore, ExploreOptions, check_property, Formula};
+use common::{relabel, nodes};
+
+fn rules_of(trace: &[(usize, Morphism)]) -> Vec<usize> {
+    trace.iter().map(|(r, _)| *r).collect()
+}
+
+#[test]
+fn test_reachability_and_invariants() {
+    // A → B → C, or A → D
+    let rules = vec![relabel("A", "B"), relabel("B", "C"), relabel("A", "D")];
+    let space = explore(&nodes(&["A"]), &rules, ExploreOptions::default()).unwrap();
+
+    let reach_c = check_property(&space, &Formula::pattern(nodes(&["C"])).ef());
+    assert!(reach_c.holds && reach_c.complete);
+    assert_eq!(rules_of(&reach_c.trace), vec![0, 1]);
+
+    let never_c = check_property(&space, &(!Formula::pattern(nodes(&["C"]))).ag());
+    assert!(!never_c.holds);
+    assert_eq!(rules_of(&never_c.trace), vec![0, 1]);
+
+    assert!(check_property(&space, &Formula::Terminal.af()).holds);
+    assert!(!check_property(&space, &Formula::pattern(nodes(&["E"])).ef()).holds);
+}
+
+#[test]
+fn test_eventually_counterexamples() {
+    let rules = vec![relabel("A", "B"), relabel("B", "C"), relabel("A", "D")];
+    let space = explore(&nodes(&["A"]), &rules, ExploreOptions::default()).unwrap();
+
+    // The branch through D never reaches C
+    let always_c = check_property(&space, &Formula::pattern(nodes(&["C"])).af());
+    assert!(!always_c.holds);
+    assert_eq!(rules_of(&always_c.trace), vec![2]);
+
+    // Toggling forever never reaches a normal form
+    let toggle = vec![relabel("A", "B"), relabel("B", "A")];
+    let space = explore(&nodes(&["A"]), &toggle, ExploreOptions::default()).unwrap();
+    let terminates = check_property(&space, &Formula::Terminal.af());
+    assert!(!terminates.holds);
+    assert_eq!(rules_of(&terminates.trace), vec![0, 1]);
+
+    let options = ExploreOptions { max_depth: 1, ..Default::default() };
+    let space = explore(&nodes(&["A"]), &toggle, options).unwrap();
+    assert!(!check_property(&space, &Formula::True).complete);
+}
@@ -0,0 +1,83 @@
+// tests/termination_test.rs
+
+mod common;
+
+use pushout::{Rule, Morphism, prove_termination, Termination, apply_once, find_matches};
+use pushout::transformation::MergePolicy;
+use petgraph::graph::Graph;
+use common::{relabel, relabel_loop};
+
+/// Delete an `e` edge between two A nodes and add an `X` node.
+fn trade_edge() -> Rule<&'static str, &'static str> {
+    let mut lhs = Graph::<&str, &str>::new();
+    let x = lhs.add_node("A");
+    let y = lhs.add_node("A");
+    lhs.add_edge(x, y, "e");
+    let mut interface = Graph::<&str, &str>::new();
+    let kx = interface.add_node("A");
+    let ky = interface.add_node("A");
+    let mut rhs = Graph::<&str, &str>::new();
+    let rx = rhs.add_node("A");
+    let ry = rhs.add_node("A");
+    rhs.add_node("X");
+    let mut l2k = Morphism::new();
+    l2k.insert_node(x, kx);
+    l2k.insert_node(y, ky);
+    let mut k2r = Morphism::new();
+    k2r.insert_node(kx, rx);
+    k2r.insert_node(ky, ry);
+    Rule::new(lhs, interface, rhs, l2k, k2r)
+}
+
+#[test]
+fn test_certificate_decreases_weight() {
+    let rules = vec![relabel("A", "B"), relabel("B", "C"), trade_edge(), relabel("X", "C")];
+    let Termination::Terminates(certificate) = prove_termination(&rules).unwrap() else {
+        panic!("expected a termination certificate");
+    };
+
+    let mut host = Graph::new();
+    let a = host.add_node("A");
+    let b = host.add_node("A");
+    host.add_node("B");
+    host.add_edge(a, b, "e");
+    for rule in &rules {
+        for m in find_matches(rule, &host, true).unwrap() {
+            let Ok(d) = apply_once(rule, &host, &m) else {
+                continue;
+            };
+            assert!(certificate.weight(&d.graph) < certificate.weight(&host));
+        }
+    }
+}
+
+#[test]
+fn test_unknown_without_weighting() {
+    let toggle = vec![relabel("A", "B"), relabel("B", "A")];
+    assert_eq!(prove_termination(&toggle).unwrap(), Termination::Unknown);
+
+    let mut rhs = Graph::<&str, &str>::new();
+    rhs.add_node("A");
+    let grow = Rule::new(Graph::new(), Graph::new(), rhs, Morphism::new(), Morphism::new());
+    assert_eq!(prove_termination(&[grow]).unwrap(), Termination::Unknown);
+}
+
+#[test]
+fn test_preserved_edges_keep_their_effective_label() {
+    let mut host = Graph::new();
+    let a = host.add_node("A");
+    host.add_edge(a, a, "e");
+
+    // Relabelled under the default policy: a certificate that holds
+    let rule = relabel_loop("e", "f");
+    let Termination::Terminates(certificate) = prove_termination(std::slice::from_ref(&rule)).unwrap() else {
+        panic!("expected a termination certificate");
+    };
+    let m = &find_matches(&rule, &host, true).unwrap()[0];
+    let d = apply_once(&rule, &host, m).unwrap();
+    assert!(certificate.weight(&d.graph) < certificate.weight(&host));
+
+    // Under `Survivor` the edge stays `e` and the rule applies forever
+    let rule = rule.with_merge_policy(MergePolicy::Survivor);
+    assert_eq!(prove_termination(&[rule]).unwrap(), Termination::Unknown);
+}